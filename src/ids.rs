@@ -0,0 +1,57 @@
+// Copyright (c) 2018 FaultyRAM
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the
+// MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at
+// your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Commonly used product, workload and component IDs.
+//!
+//! These can be passed to `Config::whitelist_product_id` and `Config::whitelist_component_id`.
+//! A complete list of valid IDs is maintained
+//! [here](https://docs.microsoft.com/en-us/visualstudio/install/workload-and-component-ids).
+
+/// The product ID for Visual Studio Enterprise.
+pub const PRODUCT_ENTERPRISE: &str = "Microsoft.VisualStudio.Product.Enterprise";
+/// The product ID for Visual Studio Professional.
+pub const PRODUCT_PROFESSIONAL: &str = "Microsoft.VisualStudio.Product.Professional";
+/// The product ID for Visual Studio Community.
+pub const PRODUCT_COMMUNITY: &str = "Microsoft.VisualStudio.Product.Community";
+/// The product ID for Visual Studio Build Tools.
+pub const PRODUCT_BUILD_TOOLS: &str = "Microsoft.VisualStudio.Product.BuildTools";
+/// The product ID for Visual Studio Team Explorer.
+pub const PRODUCT_TEAM_EXPLORER: &str = "Microsoft.VisualStudio.Product.TeamExplorer";
+/// The product ID for Visual Studio Test Agent.
+pub const PRODUCT_TEST_AGENT: &str = "Microsoft.VisualStudio.Product.TestAgent";
+/// The product ID for Visual Studio Test Controller.
+pub const PRODUCT_TEST_CONTROLLER: &str = "Microsoft.VisualStudio.Product.TestController";
+
+/// The workload ID for desktop development with C++ (IDE products only).
+pub const WORKLOAD_NATIVE_DESKTOP: &str = "Microsoft.VisualStudio.Workload.NativeDesktop";
+/// The workload ID for Visual C++ build tools (Build Tools only).
+pub const WORKLOAD_VC_TOOLS: &str = "Microsoft.VisualStudio.Workload.VCTools";
+/// The workload ID for MSBuild tools (Build Tools only).
+pub const WORKLOAD_MSBUILD_TOOLS: &str = "Microsoft.VisualStudio.Workload.MSBuildTools";
+
+/// The component ID for MSBuild.
+pub const COMPONENT_MSBUILD: &str = "Microsoft.Component.MSBuild";
+/// The component ID for the Visual C++ compiler toolset targeting x86 and x64.
+pub const COMPONENT_VC_TOOLS_X86_X64: &str = "Microsoft.VisualStudio.Component.VC.Tools.x86.x64";
+/// The component ID for the Visual C++ compiler toolset targeting ARM.
+pub const COMPONENT_VC_TOOLS_ARM: &str = "Microsoft.VisualStudio.Component.VC.Tools.ARM";
+/// The component ID for the Visual C++ compiler toolset targeting ARM64.
+pub const COMPONENT_VC_TOOLS_ARM64: &str = "Microsoft.VisualStudio.Component.VC.Tools.ARM64";
+/// The component ID for the Active Template Library (ATL).
+pub const COMPONENT_VC_ATL: &str = "Microsoft.VisualStudio.Component.VC.ATL";
+/// The component ID for the Microsoft Foundation Classes (MFC) and ATL.
+pub const COMPONENT_VC_ATL_MFC: &str = "Microsoft.VisualStudio.Component.VC.ATLMFC";
+/// The component ID for CMake support for Visual C++.
+pub const COMPONENT_VC_CMAKE: &str = "Microsoft.VisualStudio.Component.VC.CMake.Project";
+/// The component ID for the Clang compiler toolset for Visual C++.
+pub const COMPONENT_VC_LLVM_CLANG: &str = "Microsoft.VisualStudio.Component.VC.Llvm.Clang";
+/// The component ID for the Windows 10 SDK (any version).
+pub const COMPONENT_WINDOWS_10_SDK: &str = "Microsoft.VisualStudio.Component.Windows10SDK";
+/// The component ID for the Windows 11 SDK (version 10.0.22621.0).
+pub const COMPONENT_WINDOWS_11_SDK_22621: &str =
+    "Microsoft.VisualStudio.Component.Windows11SDK.22621";
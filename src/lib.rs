@@ -8,11 +8,9 @@
 
 //! Provides support for invoking and capturing the output of the vswhere utility.
 
-#![cfg(target_os = "windows")]
-#![forbid(warnings)]
+#![deny(warnings)]
 #![forbid(future_incompatible)]
 #![deny(unused)]
-#![forbid(box_pointers)]
 #![forbid(missing_copy_implementations)]
 #![forbid(missing_debug_implementations)]
 #![forbid(missing_docs)]
@@ -22,44 +20,84 @@
 #![deny(unused_qualifications)]
 #![forbid(unused_results)]
 #![forbid(variant_size_differences)]
-#![cfg_attr(feature = "cargo-clippy", forbid(clippy))]
-#![cfg_attr(feature = "cargo-clippy", deny(clippy_pedantic))]
-#![cfg_attr(feature = "cargo-clippy", forbid(clippy_cargo))]
-#![cfg_attr(feature = "cargo-clippy", forbid(clippy_complexity))]
-#![cfg_attr(feature = "cargo-clippy", deny(clippy_correctness))]
-#![cfg_attr(feature = "cargo-clippy", deny(clippy_perf))]
-#![cfg_attr(feature = "cargo-clippy", forbid(clippy_style))]
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+#![deny(clippy::cargo)]
+#![forbid(clippy::complexity)]
+#![deny(clippy::correctness)]
+#![deny(clippy::perf)]
+#![forbid(clippy::style)]
+// Duplicated crates come from dependencies, and this lint can only be allowed crate-wide.
+#![allow(clippy::multiple_crate_versions)]
 
 extern crate chrono;
+#[cfg(feature = "tokio")]
+extern crate futures;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
 extern crate semver;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+#[cfg(feature = "tokio")]
+extern crate tokio;
 extern crate url;
-extern crate url_serde;
+#[cfg(windows)]
 extern crate winapi;
 
 use chrono::offset::Utc;
 use chrono::DateTime;
+#[cfg(feature = "tokio")]
+use futures::future::{self, BoxFuture, Either, FutureExt, TryFutureExt};
 use semver::Version;
-use serde::de::{Unexpected, Visitor};
+use serde::de::{DeserializeOwned, IgnoredAny, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::ffi::OsString;
-use std::fmt::{self, Display, Formatter};
-use std::io::{self, ErrorKind};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::convert::TryFrom;
+use std::env;
+use std::error;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display, Formatter, Write};
+use std::fs;
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io::{self, ErrorKind, Read};
 use std::iter;
-use std::ops::Range;
-use std::os::windows::ffi::OsStringExt;
+use std::ops::{Bound, Range, RangeBounds};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::ptr;
-use std::slice;
-use std::str;
+use std::process::{self, Child, Command, ExitStatus, Output, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "tokio")]
+use std::vec;
+#[cfg(feature = "tokio")]
+use tokio::process::Command as AsyncCommand;
+#[cfg(feature = "tokio")]
+use tokio::{task, time};
 use url::Url;
 
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {};
+}
+
+pub mod ids;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 /// A version number that consists of four integers, widely used within the Windows world.
+///
+/// Version numbers are displayed in full (e.g. `16.11.2.0`) by default. The alternate flag
+/// (`{:#}`) displays only the major and minor version numbers instead (e.g. `16.11`).
 pub struct FourPointVersion {
     major: u16,
     minor: u16,
@@ -67,21 +105,286 @@ pub struct FourPointVersion {
     build: u16,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+/// An error that can occur when parsing a `FourPointVersion` from a string.
+pub struct ParseFourPointVersionError {
+    input: String,
+    kind: ParseFourPointVersionErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ParseFourPointVersionErrorKind {
+    Length(usize),
+    Invalid(usize),
+    Overflow(usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A set of component IDs, along with how they are used to filter search results.
+pub enum RequiresFilter<'a> {
+    /// Exclude installations that do not provide every listed component.
+    All(&'a [&'a str]),
+    /// Exclude installations that do not provide at least one listed component.
+    Any(&'a [&'a str]),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A commonly used Visual Studio component.
+pub enum Component {
+    /// MSBuild.
+    MSBuild,
+    /// The Visual C++ compiler toolset targeting x86 and x64.
+    VcToolsX86X64,
+    /// The Visual C++ compiler toolset targeting ARM.
+    VcToolsArm,
+    /// The Visual C++ compiler toolset targeting ARM64.
+    VcToolsArm64,
+    /// The Active Template Library (ATL).
+    VcAtl,
+    /// The Microsoft Foundation Classes (MFC) and ATL.
+    VcAtlMfc,
+    /// CMake support for Visual C++.
+    VcCMake,
+    /// The Clang compiler toolset for Visual C++.
+    VcLlvmClang,
+    /// The Windows 10 SDK (any version).
+    Windows10Sdk,
+    /// The Windows 11 SDK (version 10.0.22621.0).
+    Windows11Sdk22621,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A processor architecture that the Visual C++ tools run on or target.
+pub enum Arch {
+    /// 32-bit x86.
+    X86,
+    /// x86-64 (also known as AMD64 or x64).
+    X64,
+    /// 32-bit ARM. The Visual C++ tools can target ARM, but do not run on it.
+    Arm,
+    /// 64-bit ARM.
+    Arm64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A release channel from which Visual Studio is installed and updated.
+pub enum Channel {
+    /// The release channel, which only provides stable versions.
+    Release,
+    /// The preview channel, which provides pre-release versions ahead of their release.
+    Preview,
+    /// The internal preview channel, which is only available within Microsoft.
+    IntPreview,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A Visual Studio product, identified by its edition.
+pub enum Edition {
+    /// Visual Studio Enterprise.
+    Enterprise,
+    /// Visual Studio Professional.
+    Professional,
+    /// Visual Studio Community.
+    Community,
+    /// Visual Studio Build Tools.
+    BuildTools,
+    /// Visual Studio Team Explorer.
+    TeamExplorer,
+    /// Visual Studio Test Agent.
+    TestAgent,
+    /// Visual Studio Test Controller.
+    TestController,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// A Visual Studio product line, named after the year in which it was introduced.
+pub enum ProductLine {
+    /// Visual Studio 2017 (version 15).
+    Vs2017,
+    /// Visual Studio 2019 (version 16).
+    Vs2019,
+    /// Visual Studio 2022 (version 17).
+    Vs2022,
+    /// Visual Studio 2026 (version 18).
+    Vs2026,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+/// Specifies how pre-release versions of Visual Studio are treated in search results.
+pub enum PrereleaseFilter {
+    /// Exclude pre-release versions from search results.
+    Exclude,
+    /// Include both pre-release and release versions in search results.
+    Include,
+    /// Include only pre-release versions in search results.
+    ///
+    /// vswhere is unable to perform this filtering by itself, so it is instead performed after
+    /// vswhere has run. Consequently this is treated the same as `Include` whenever the output
+    /// of vswhere does not describe whole installations, such as when searching for files or
+    /// retrieving single properties.
+    ///
+    /// For the same reason, this cannot be combined with `Config::only_latest_versions`: vswhere
+    /// would pick the newest installation before release versions are filtered out, and so
+    /// return nothing if the newest installation is a release version. `Config::validate`
+    /// rejects that combination, including in helpers that enable `only_latest_versions`
+    /// themselves such as `Config::find_msbuild_custom_path`. `Config::run_latest_custom_path`
+    /// instead picks the newest pre-release version after filtering.
+    Only,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// An output format supported by vswhere.
+pub enum OutputFormat {
+    /// JSON output, as used by all typed queries.
+    Json,
+    /// Human-readable text output, with one `name: value` pair per line.
+    Text,
+    /// Bare values with one per line, typically used alongside `-property`.
+    Value,
+    /// XML output.
+    Xml,
+}
+
+#[derive(Debug)]
+/// An error that can occur when running vswhere.
+pub enum Error {
+    /// vswhere could not be found.
+    ///
+    /// This only ever means that the vswhere executable is missing. If vswhere runs successfully
+    /// but no installations match, an empty result (or `None`) is returned instead.
+    NotFound,
+    /// An I/O error occurred while locating or starting vswhere.
+    Spawn(io::Error),
+    /// vswhere ran, but exited unsuccessfully.
+    Exit {
+        /// The exit code returned by vswhere, if any.
+        code: Option<i32>,
+        /// Any output that vswhere wrote to standard error.
+        stderr: String,
+    },
+    /// vswhere did not finish running within the configured timeout, and was terminated.
+    TimedOut,
+    /// The output of vswhere could not be parsed.
+    Parse(serde_json::Error),
+    /// vswhere found more than one installation where at most one was expected.
+    ///
+    /// The number of installations that were found is included.
+    Ambiguous(usize),
+    /// The configuration could never match any installation, so vswhere was not run.
+    Config(ConfigError),
+    /// vswhere's version number could not be found in the banner that it printed.
+    ///
+    /// The banner is included.
+    UnknownVersion(String),
+    /// An I/O error occurred while writing an embedded copy of vswhere to disk.
+    Extract(io::Error),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A problem with a `Config` that would make vswhere return no results or fail, detected by
+/// `Config::validate` or `Config::validate_for`.
+pub enum ConfigError {
+    /// The lower bound of the version number range is greater than its upper bound.
+    InvertedVersionRange(FourPointVersion, FourPointVersion),
+    /// The bounds of the version number range are equal, but at least one of them is exclusive,
+    /// so no version number lies within it.
+    EmptyVersionRange(FourPointVersion),
+    /// A flag is not supported by the version of vswhere being used.
+    ///
+    /// The flag is included, along with the minimum version of vswhere that supports it.
+    UnsupportedFlag(&'static str, Version),
+    /// `PrereleaseFilter::Only` was combined with `Config::only_latest_versions`, which would
+    /// discard every pre-release version if the newest installation is a release version.
+    LatestWithPrereleaseOnly,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum KnownFolder {
+    ProgramData,
+    ProgramFiles,
+    ProgramFilesX86,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
 /// Builder-style configuration for a vswhere instance.
+///
+/// A `Config` can be serialized (e.g. to record the exact query used by a build) and
+/// deserialized later to repeat it. Fields that are missing when deserializing take their
+/// default values. Note that raw arguments and environment variables are serialized in a
+/// platform-specific form, and so can only be deserialized on the same kind of platform.
 pub struct Config {
-    prerelease: bool,
+    prerelease: PrereleaseFilter,
     products: Vec<String>,
     requires: Vec<String>,
     requires_any: bool,
-    version: Option<Range<FourPointVersion>>,
+    version: Option<(Bound<FourPointVersion>, Bound<FourPointVersion>)>,
+    all: bool,
     latest: bool,
+    sort: bool,
+    sort_locally: bool,
+    prefer_64bit: bool,
+    timeout: Option<Duration>,
+    utf8: bool,
+    code_page: Option<u32>,
+    color: bool,
+    raw_args: Vec<OsString>,
+    env_clear: bool,
+    envs: Vec<(OsString, Option<OsString>)>,
+    current_dir: Option<PathBuf>,
+    #[serde(skip)]
+    runner: Option<Arc<dyn Runner>>,
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(similar_names))]
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A vswhere executable whose location has been resolved ahead of time.
+///
+/// Each of the `Config::run_default_path` family of methods searches for vswhere every time it
+/// is called; `VsWhere` instead performs this search once, and reuses its result for every
+/// subsequent invocation.
+pub struct VsWhere {
+    path: PathBuf,
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// A means of running vswhere and capturing its output.
+///
+/// `Config` uses `ProcessRunner` by default, which spawns vswhere as a child process. Other
+/// implementations can be supplied via `Config::runner`, e.g. `MockRunner` to return canned
+/// output in tests that must run without Visual Studio installed.
+pub trait Runner: fmt::Debug + Send + Sync {
+    /// Runs the given command, returning its exit status and captured output.
+    ///
+    /// If `timeout` is `Some`, implementations should give up and return `Error::TimedOut` once
+    /// the specified amount of time has elapsed.
+    fn output(&self, cmd: &mut Command, timeout: Option<Duration>) -> Result<Output, Error>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+/// A `Runner` that spawns vswhere as a child process.
+pub struct ProcessRunner;
+
+#[cfg(any(unix, windows))]
+#[derive(Clone, Debug, Default)]
+/// A `Runner` that returns canned output instead of running vswhere.
+///
+/// Every command is treated as if vswhere had run and produced the configured output, which
+/// allows testing code that depends on vswhere without Visual Studio being installed.
+pub struct MockRunner {
+    code: i32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+#[allow(clippy::similar_names, clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// Information about a Visual Studio installation.
+///
+/// Two `InstallInfo`s are considered equal if they have the same instance ID, regardless of
+/// any other details (which may differ between queries, e.g. after an update). This allows
+/// results from several queries to be merged using a `HashSet`.
 pub struct InstallInfo {
     instance_id: String,
     install_date: DateTime<Utc>,
@@ -91,6 +394,12 @@ pub struct InstallInfo {
     product_id: String,
     product_path: PathBuf,
     is_prerelease: bool,
+    #[serde(default = "default_true")]
+    is_complete: bool,
+    #[serde(default = "default_true")]
+    is_launchable: bool,
+    #[serde(default)]
+    is_reboot_required: bool,
     display_name: String,
     description: String,
     channel_id: String,
@@ -105,9 +414,11 @@ pub struct InstallInfo {
     update_date: DateTime<Utc>,
     catalog: InstallCatalog,
     properties: InstallProperties,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(similar_names))]
+#[allow(clippy::similar_names)]
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// Catalog information for a Visual Studio installation.
@@ -131,6 +442,8 @@ pub struct InstallCatalog {
     product_release: String,
     product_semantic_version: Version,
     required_engine_version: FourPointVersion,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -141,6 +454,27 @@ pub struct InstallProperties {
     channel_manifest_id: String,
     nickname: String,
     setup_engine_file_path: PathBuf,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+/// The tools needed to build native code, resolved from a single Visual Studio installation.
+///
+/// This is returned by `Config::find_vs_tools_custom_path` and related methods. Each tool is
+/// located by checking for its file beneath the installation path, so tools that are not
+/// installed are reported as `None` rather than causing an error. When comparing `VsTools`, the
+/// installations are compared by instance ID only, as for `InstallInfo`.
+pub struct VsTools {
+    instance: InstallInfo,
+    msbuild: Option<PathBuf>,
+    vcvarsall: Option<PathBuf>,
+    vc_tools_version: Option<String>,
+    host_compiler: Option<PathBuf>,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn deserialize_uppercase_bool<'de, D: Deserializer<'de>>(
@@ -148,7 +482,7 @@ fn deserialize_uppercase_bool<'de, D: Deserializer<'de>>(
 ) -> Result<bool, D::Error> {
     struct UppercaseBoolVisitor;
 
-    impl<'de> Visitor<'de> for UppercaseBoolVisitor {
+    impl Visitor<'_> for UppercaseBoolVisitor {
         type Value = bool;
 
         fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
@@ -171,7 +505,7 @@ fn deserialize_uppercase_bool<'de, D: Deserializer<'de>>(
     deserializer.deserialize_str(UppercaseBoolVisitor)
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+#[allow(clippy::trivially_copy_pass_by_ref)]
 fn serialize_uppercase_bool<S: Serializer>(
     boolean: &bool,
     serializer: S,
@@ -183,9 +517,533 @@ fn serialize_uppercase_bool<S: Serializer>(
     }
 }
 
+#[cfg(windows)]
+fn get_known_folder_path(folder: KnownFolder) -> Result<PathBuf, Error> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use std::ptr;
+    use std::slice;
+    use winapi::ctypes::c_void;
+    use winapi::shared::ntdef::PWSTR;
+    use winapi::shared::winerror::S_OK;
+    use winapi::um::combaseapi::CoTaskMemFree;
+    use winapi::um::knownfolders::{
+        FOLDERID_ProgramData, FOLDERID_ProgramFiles, FOLDERID_ProgramFilesX86,
+    };
+    use winapi::um::shlobj::SHGetKnownFolderPath;
+
+    struct KnownFolderPath(PWSTR);
+
+    impl Drop for KnownFolderPath {
+        fn drop(&mut self) {
+            unsafe {
+                CoTaskMemFree(self.0.cast::<c_void>());
+            }
+        }
+    }
+
+    let env_var = match folder {
+        KnownFolder::ProgramData => "ProgramData",
+        KnownFolder::ProgramFiles => "ProgramFiles",
+        KnownFolder::ProgramFilesX86 => "ProgramFiles(x86)",
+    };
+    if let Some(path) = env::var_os(env_var).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let id = match folder {
+        KnownFolder::ProgramData => &FOLDERID_ProgramData,
+        KnownFolder::ProgramFiles => &FOLDERID_ProgramFiles,
+        KnownFolder::ProgramFilesX86 => &FOLDERID_ProgramFilesX86,
+    };
+    unsafe {
+        let mut path = KnownFolderPath(ptr::null_mut());
+        let hres = SHGetKnownFolderPath(id, 0, ptr::null_mut(), ptr::addr_of_mut!(path.0));
+        if hres == S_OK {
+            let mut wide_string = path.0;
+            let mut len = 0;
+            while wide_string.read() != 0 {
+                wide_string = wide_string.add(1);
+                len += 1;
+            }
+            let ws_slice = slice::from_raw_parts(path.0, len);
+            let os_string = OsString::from_wide(ws_slice);
+            Ok(Path::new(&os_string).to_owned())
+        } else {
+            Err(Error::Spawn(io::Error::last_os_error()))
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn get_known_folder_path(_: KnownFolder) -> Result<PathBuf, Error> {
+    Err(Error::Spawn(io::Error::new(
+        ErrorKind::Unsupported,
+        "default vswhere locations are only known on Windows",
+    )))
+}
+
+fn override_path() -> Option<PathBuf> {
+    env::var_os("VSWHERE_PATH")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+fn default_paths() -> Result<Vec<PathBuf>, Error> {
+    match override_path() {
+        Some(path) => Ok(vec![path]),
+        None => known_folder_paths(),
+    }
+}
+
+fn known_folder_paths() -> Result<Vec<PathBuf>, Error> {
+    let candidates = [
+        (KnownFolder::ProgramData, r"chocolatey\bin\vswhere.exe"),
+        (
+            KnownFolder::ProgramFilesX86,
+            r"Microsoft Visual Studio\Installer\vswhere.exe",
+        ),
+        (
+            KnownFolder::ProgramFiles,
+            r"Microsoft Visual Studio\Installer\vswhere.exe",
+        ),
+    ];
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(candidates.len());
+    let mut first_error = None;
+    for &(folder, suffix) in &candidates {
+        match get_known_folder_path(folder) {
+            Ok(dir) => {
+                let path = dir.join(suffix);
+                if path.is_dir() {
+                    debug!("skipping {}, which is a directory", path.display());
+                } else if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+            Err(e) => {
+                debug!("failed to locate known folder {folder:?}: {e}");
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+        }
+    }
+    match first_error {
+        Some(e) if paths.is_empty() => Err(e),
+        _ => Ok(paths),
+    }
+}
+
+fn locate_default_path() -> Result<PathBuf, Error> {
+    if let Some(path) = override_path() {
+        return Ok(path);
+    }
+    default_paths()?
+        .into_iter()
+        .find(|path| {
+            let found = path.is_file();
+            if !found {
+                debug!("vswhere not found at {}", path.display());
+            }
+            found
+        })
+        .ok_or(Error::NotFound)
+}
+
+fn with_default_path<T, F: Fn(PathBuf) -> Result<T, Error>>(run: F) -> Result<T, Error> {
+    let mut last_error = Error::NotFound;
+    for path in default_paths()? {
+        debug!("trying vswhere at {}", path.display());
+        match run(path) {
+            Err(e) if is_unusable_candidate(&e) => skip_candidate(e, &mut last_error),
+            result => return result,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(feature = "tokio")]
+fn with_default_path_async<'a, F, R, T>(run: F) -> BoxFuture<'a, Result<T, Error>>
+where
+    F: Fn(PathBuf) -> R + Send + 'a,
+    R: Future<Output = Result<T, Error>> + Send + 'a,
+    T: Send + 'a,
+{
+    match default_paths() {
+        Ok(paths) => try_candidates_async(paths.into_iter(), Error::NotFound, run),
+        Err(e) => future::err(e).boxed(),
+    }
+}
+
+#[cfg(feature = "tokio")]
+fn try_candidates_async<'a, F, R, T>(
+    mut paths: vec::IntoIter<PathBuf>,
+    mut last_error: Error,
+    run: F,
+) -> BoxFuture<'a, Result<T, Error>>
+where
+    F: Fn(PathBuf) -> R + Send + 'a,
+    R: Future<Output = Result<T, Error>> + Send + 'a,
+    T: Send + 'a,
+{
+    let Some(path) = paths.next() else {
+        return future::err(last_error).boxed();
+    };
+    debug!("trying vswhere at {}", path.display());
+    run(path)
+        .then(move |result| match result {
+            Err(e) if is_unusable_candidate(&e) => {
+                skip_candidate(e, &mut last_error);
+                try_candidates_async(paths, last_error, run)
+            }
+            result => future::ready(result).boxed(),
+        })
+        .boxed()
+}
+
+#[cfg(feature = "tokio")]
+fn spawn_async(
+    mut cmd: Command,
+    runner: Option<Arc<dyn Runner>>,
+    timeout: Option<Duration>,
+) -> impl Future<Output = Result<Output, Error>> {
+    debug!("running {cmd:?}");
+    if let Some(runner) = runner {
+        // Custom runners are synchronous, so keep them off the async worker threads.
+        return Either::Left(
+            task::spawn_blocking(move || runner.output(&mut cmd, timeout))
+                .map(|result| result.unwrap_or_else(|e| Err(Error::Spawn(io::Error::other(e))))),
+        );
+    }
+    // Dropping the child on timeout kills it, like `output_with_timeout`.
+    let output = AsyncCommand::from(cmd)
+        .kill_on_drop(true)
+        .output()
+        .map_err(spawn_error);
+    Either::Right(match timeout {
+        Some(timeout) => Either::Left(
+            time::timeout(timeout, output).map(|result| result.unwrap_or(Err(Error::TimedOut))),
+        ),
+        None => Either::Right(output),
+    })
+}
+
+/// Returns whether an error means that a vswhere candidate cannot be run at all, in which case
+/// the next candidate should be tried, as opposed to an error that should stop the search.
+fn is_unusable_candidate(e: &Error) -> bool {
+    #[cfg(windows)]
+    fn is_bad_executable(code: i32) -> bool {
+        use winapi::shared::winerror::{ERROR_BAD_EXE_FORMAT, ERROR_EXE_MACHINE_TYPE_MISMATCH};
+
+        u32::try_from(code).is_ok_and(|code| {
+            code == ERROR_BAD_EXE_FORMAT || code == ERROR_EXE_MACHINE_TYPE_MISMATCH
+        })
+    }
+
+    #[cfg(unix)]
+    fn is_bad_executable(code: i32) -> bool {
+        // ENOEXEC, which has the same value on every Unix-like platform.
+        code == 8
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn is_bad_executable(_: i32) -> bool {
+        false
+    }
+
+    match *e {
+        Error::NotFound => true,
+        Error::Spawn(ref e) => {
+            e.kind() == ErrorKind::PermissionDenied
+                || e.raw_os_error().is_some_and(is_bad_executable)
+        }
+        _ => false,
+    }
+}
+
+fn skip_candidate(e: Error, last_error: &mut Error) {
+    debug!("skipping unusable vswhere: {e}");
+    if !matches!(e, Error::NotFound) {
+        *last_error = e;
+    }
+}
+
+fn spawn_error(e: io::Error) -> Error {
+    debug!("failed to run vswhere: {e}");
+    if e.kind() == ErrorKind::NotFound {
+        Error::NotFound
+    } else {
+        Error::Spawn(e)
+    }
+}
+
+#[cfg(windows)]
+fn decode_code_page(bytes: &[u8], code_page: u32) -> String {
+    use std::ptr;
+    use winapi::um::stringapiset::MultiByteToWideChar;
+
+    let len = match i32::try_from(bytes.len()) {
+        Ok(0) | Err(_) => return String::from_utf8_lossy(bytes).into_owned(),
+        Ok(len) => len,
+    };
+    unsafe {
+        let wide_len =
+            MultiByteToWideChar(code_page, 0, bytes.as_ptr().cast(), len, ptr::null_mut(), 0);
+        let mut wide = vec![0; usize::try_from(wide_len).unwrap_or(0)];
+        if wide.is_empty() {
+            return String::from_utf8_lossy(bytes).into_owned();
+        }
+        let written = MultiByteToWideChar(
+            code_page,
+            0,
+            bytes.as_ptr().cast(),
+            len,
+            wide.as_mut_ptr(),
+            wide_len,
+        );
+        wide.truncate(usize::try_from(written).unwrap_or(0));
+        String::from_utf16_lossy(&wide)
+    }
+}
+
+#[cfg(not(windows))]
+fn decode_code_page(bytes: &[u8], _: u32) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Decodes the output of vswhere, which is encoded as UTF-8 if `code_page` is `None`.
+fn decode_stdout(stdout: &[u8], code_page: Option<u32>) -> Cow<'_, str> {
+    match code_page {
+        None => String::from_utf8_lossy(stdout),
+        Some(code_page) => Cow::Owned(decode_code_page(stdout, code_page)),
+    }
+}
+
+/// Parses the output of vswhere, which is encoded as UTF-8 if `code_page` is `None`.
+fn parse_output<T: DeserializeOwned>(output: &Output, code_page: Option<u32>) -> Result<T, Error> {
+    successful_stdout(output).and_then(|stdout| match code_page {
+        None => parse_instances_as(stdout),
+        Some(code_page) => parse_instances_as(decode_code_page(stdout, code_page).as_bytes()),
+    })
+}
+
+fn successful_stdout(output: &Output) -> Result<&[u8], Error> {
+    debug!("vswhere exited with {}", output.status);
+    if output.status.success() {
+        Ok(&output.stdout)
+    } else {
+        Err(Error::Exit {
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+fn output_with_timeout(cmd: &mut Command, timeout: Duration) -> Result<Output, Error> {
+    type PipeResult = (usize, io::Result<Vec<u8>>);
+
+    fn read_in_background<R: Read + Send + 'static>(
+        pipe: Option<R>,
+        index: usize,
+        tx: mpsc::Sender<PipeResult>,
+    ) {
+        let _ = thread::spawn(move || {
+            let mut buf = Vec::new();
+            let result = match pipe {
+                Some(mut pipe) => pipe.read_to_end(&mut buf).map(|_| buf),
+                None => Ok(buf),
+            };
+            let _ = tx.send((index, result));
+        });
+    }
+
+    /// Terminates vswhere and waits for it to exit, returning the error that caused this.
+    fn kill(child: &mut Child, rx: &mpsc::Receiver<PipeResult>, error: Error) -> Error {
+        let _ = child.kill();
+        let _ = child.wait();
+        debug!("terminated vswhere: {error}");
+        // Any processes started by vswhere that inherited its pipes are not terminated, and keep
+        // the reader threads blocked until they exit; give up on the readers after a short
+        // grace period rather than waiting for them indefinitely.
+        let grace = Instant::now() + Duration::from_millis(100);
+        while rx
+            .recv_timeout(grace.saturating_duration_since(Instant::now()))
+            .is_ok()
+        {}
+        error
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    let (tx, rx) = mpsc::channel();
+    read_in_background(child.stdout.take(), 0, tx.clone());
+    read_in_background(child.stderr.take(), 1, tx);
+    let deadline = Instant::now() + timeout;
+    // The output is complete once both pipes have been closed, which is normally when vswhere
+    // exits, so wait for that rather than polling the child.
+    let mut pipes = [None, None];
+    while pipes.iter().any(Option::is_none) {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((index, Ok(buf))) => pipes[index] = Some(buf),
+            Ok((_, Err(e))) => return Err(kill(&mut child, &rx, Error::Spawn(e))),
+            Err(_) => return Err(kill(&mut child, &rx, Error::TimedOut)),
+        }
+    }
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => (),
+            Err(e) => return Err(kill(&mut child, &rx, Error::Spawn(e))),
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(kill(&mut child, &rx, Error::TimedOut));
+        }
+        thread::sleep(cmp::min(deadline - now, Duration::from_millis(10)));
+    };
+    let [stdout, stderr] = pipes;
+    Ok(Output {
+        status,
+        stdout: stdout.unwrap_or_default(),
+        stderr: stderr.unwrap_or_default(),
+    })
+}
+
+fn version_range_arg(start: Bound<FourPointVersion>, end: Bound<FourPointVersion>) -> String {
+    // Writing to a `String` cannot fail.
+    let mut arg = String::new();
+    let _ = match start {
+        Bound::Included(v) => write!(arg, "[{v},"),
+        Bound::Excluded(v) => write!(arg, "({v},"),
+        Bound::Unbounded => arg.write_str("(,"),
+    };
+    let _ = match end {
+        Bound::Included(v) => write!(arg, "{v}]"),
+        Bound::Excluded(v) => write!(arg, "{v})"),
+        Bound::Unbounded => arg.write_char(')'),
+    };
+    arg
+}
+
+fn flag_min_version(flag: &str) -> Option<(&'static str, Version)> {
+    const FLAGS: [(&str, u64, u64, u64); 6] = [
+        ("-prerelease", 2, 0, 2),
+        ("-requiresAny", 2, 3, 2),
+        ("-utf8", 2, 5, 2),
+        ("-find", 2, 6, 7),
+        ("-sort", 2, 7, 1),
+        ("-nocolor", 2, 8, 4),
+    ];
+    let name = flag.strip_prefix(['-', '/'])?;
+    FLAGS
+        .iter()
+        .find(|(known, ..)| known[1..].eq_ignore_ascii_case(name))
+        .map(|&(known, major, minor, patch)| (known, Version::new(major, minor, patch)))
+}
+
+fn parse_vswhere_version(banner: &str) -> Option<Version> {
+    let mut words = banner.split_whitespace();
+    let _ = words
+        .by_ref()
+        .find(|word| word.eq_ignore_ascii_case("version"))?;
+    words.next().and_then(|word| Version::parse(word).ok())
+}
+
+fn quote_program_name(name: &str, out: &mut String) {
+    // `CreateProcess` takes everything up to the next quote literally, so unlike other arguments,
+    // backslashes are never escaped.
+    if name.is_empty() || name.contains([' ', '\t']) {
+        out.push('"');
+        out.push_str(name);
+        out.push('"');
+    } else {
+        out.push_str(name);
+    }
+}
+
+fn quote_windows_arg(arg: &str, out: &mut String) {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '"']) {
+        out.push_str(arg);
+        return;
+    }
+    out.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                continue;
+            }
+            '"' => out.extend(iter::repeat_n('\\', backslashes * 2 + 1)),
+            _ => out.extend(iter::repeat_n('\\', backslashes)),
+        }
+        backslashes = 0;
+        out.push(c);
+    }
+    out.extend(iter::repeat_n('\\', backslashes * 2));
+    out.push('"');
+}
+
+fn path_contains(root: &Path, path: &Path) -> bool {
+    let mut path = path.components();
+    root.components().all(|root| {
+        path.next()
+            .is_some_and(|c| c.as_os_str().eq_ignore_ascii_case(root.as_os_str()))
+    })
+}
+
+fn path_eq(a: &Path, b: &Path) -> bool {
+    a.components().count() == b.components().count() && path_contains(a, b)
+}
+
+fn single_instance(mut instances: Vec<InstallInfo>) -> Result<Option<InstallInfo>, Error> {
+    if instances.len() > 1 {
+        Err(Error::Ambiguous(instances.len()))
+    } else {
+        Ok(instances.pop())
+    }
+}
+
+fn push_id(ids: &mut Vec<String>, id: String) {
+    if !id.trim().is_empty() {
+        ids.push(id);
+    }
+}
+
+fn filter_instances(
+    prerelease: PrereleaseFilter,
+    sort_locally: bool,
+    mut instances: Vec<InstallInfo>,
+) -> Vec<InstallInfo> {
+    if prerelease == PrereleaseFilter::Only {
+        instances.retain(InstallInfo::is_prerelease);
+    }
+    if sort_locally {
+        instances.sort_by(|a, b| {
+            (b.installation_version, b.install_date).cmp(&(a.installation_version, a.install_date))
+        });
+    }
+    instances
+}
+
+fn property_values(instances: Vec<Map<String, Value>>) -> Vec<String> {
+    instances
+        .into_iter()
+        .map(|instance| match instance.into_iter().next() {
+            Some((_, Value::String(s))) => s,
+            Some((_, Value::Null)) | None => String::new(),
+            Some((_, v)) => v.to_string(),
+        })
+        .collect()
+}
+
+#[allow(clippy::must_use_candidate)]
 impl FourPointVersion {
     /// Creates a new version number using the given values.
-    pub fn new(major: u16, minor: u16, revision: u16, build: u16) -> Self {
+    pub const fn new(major: u16, minor: u16, revision: u16, build: u16) -> Self {
         Self {
             major,
             minor,
@@ -194,32 +1052,76 @@ impl FourPointVersion {
         }
     }
 
+    /// Creates a new version number using the given major and minor version numbers, with the
+    /// revision and build numbers set to zero.
+    pub const fn major_minor(major: u16, minor: u16) -> Self {
+        Self::new(major, minor, 0, 0)
+    }
+
     /// Returns the major version number.
     pub fn major(self) -> u16 {
         self.major
     }
 
-    /// Returns the major version number.
+    /// Returns the minor version number.
     pub fn minor(self) -> u16 {
         self.minor
     }
 
-    /// Returns the major version number.
+    /// Returns the revision number.
     pub fn revision(self) -> u16 {
         self.revision
     }
 
-    /// Returns the major version number.
+    /// Returns the build number.
     pub fn build(self) -> u16 {
         self.build
     }
+
+    /// Returns the version number that immediately precedes this one, or `None` if this is
+    /// `0.0.0.0`.
+    ///
+    /// For example, the predecessor of `17.0.0.0` is `16.65535.65535.65535`. This is useful for
+    /// turning an exclusive upper bound into an inclusive one.
+    pub fn predecessor(self) -> Option<Self> {
+        self.to_u64().checked_sub(1).map(Self::from_u64)
+    }
+
+    /// Returns the version number that immediately follows this one, or `None` if this is
+    /// `65535.65535.65535.65535`.
+    ///
+    /// For example, the successor of `16.65535.65535.65535` is `17.0.0.0`.
+    pub fn successor(self) -> Option<Self> {
+        self.to_u64().checked_add(1).map(Self::from_u64)
+    }
+
+    fn to_u64(self) -> u64 {
+        u64::from(self.major) << 48
+            | u64::from(self.minor) << 32
+            | u64::from(self.revision) << 16
+            | u64::from(self.build)
+    }
+
+    fn from_u64(value: u64) -> Self {
+        let bytes = value.to_be_bytes();
+        let part = |index: usize| u16::from_be_bytes([bytes[index * 2], bytes[index * 2 + 1]]);
+        Self::new(part(0), part(1), part(2), part(3))
+    }
+
+    /// Parses a version number from a JSON value, such as the `installationVersion` field of an
+    /// installation retrieved via `Config::run_custom_path_as`.
+    ///
+    /// `None` is returned if the value is not a string or is not a valid version number.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().and_then(|s| s.parse().ok())
+    }
 }
 
 impl<'de> Deserialize<'de> for FourPointVersion {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         struct FourPointVersionVisitor;
 
-        impl<'de> Visitor<'de> for FourPointVersionVisitor {
+        impl Visitor<'_> for FourPointVersionVisitor {
             type Value = FourPointVersion;
 
             fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
@@ -230,26 +1132,8 @@ impl<'de> Deserialize<'de> for FourPointVersion {
             }
 
             fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
-                fn parse_number<E: serde::de::Error>(
-                    visitor: &FourPointVersionVisitor,
-                    chunk: &str,
-                ) -> Result<u16, E> {
-                    u16::from_str_radix(chunk, 10)
-                        .map_err(|_| E::invalid_value(Unexpected::Str(chunk), visitor))
-                }
-                let iter = v.split('.');
-                let len = iter.clone().count();
-                if len < 1 || len > 4 {
-                    Err(E::invalid_length(len, &self))
-                } else {
-                    let mut version_getter = iter.chain(iter::repeat("0"));
-                    Ok(FourPointVersion::new(
-                        parse_number(&self, version_getter.next().unwrap())?,
-                        parse_number(&self, version_getter.next().unwrap())?,
-                        parse_number(&self, version_getter.next().unwrap())?,
-                        parse_number(&self, version_getter.next().unwrap())?,
-                    ))
-                }
+                v.parse()
+                    .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
             }
         }
 
@@ -259,11 +1143,46 @@ impl<'de> Deserialize<'de> for FourPointVersion {
 
 impl Display for FourPointVersion {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}.{}.{}.{}",
-            self.major, self.minor, self.revision, self.build
-        )
+        if f.alternate() {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(
+                f,
+                "{}.{}.{}.{}",
+                self.major, self.minor, self.revision, self.build
+            )
+        }
+    }
+}
+
+impl FromStr for FourPointVersion {
+    type Err = ParseFourPointVersionError;
+
+    /// Parses a version number from one to four integers separated by a period (`.`).
+    ///
+    /// Any omitted integers are assumed to be zero, such that `"15.7"` is equivalent to
+    /// `"15.7.0.0"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |kind| ParseFourPointVersionError {
+            input: s.to_owned(),
+            kind,
+        };
+        let iter = s.split('.');
+        let len = iter.clone().count();
+        if len > 4 {
+            return Err(error(ParseFourPointVersionErrorKind::Length(len)));
+        }
+        let mut numbers = [0; 4];
+        for (index, chunk) in iter.enumerate() {
+            numbers[index] = chunk.parse().map_err(|_| {
+                if !chunk.is_empty() && chunk.bytes().all(|b| b.is_ascii_digit()) {
+                    error(ParseFourPointVersionErrorKind::Overflow(index))
+                } else {
+                    error(ParseFourPointVersionErrorKind::Invalid(index))
+                }
+            })?;
+        }
+        Ok(Self::new(numbers[0], numbers[1], numbers[2], numbers[3]))
     }
 }
 
@@ -273,73 +1192,492 @@ impl Serialize for FourPointVersion {
     }
 }
 
-impl Config {
-    /// Creates a new `Config` instance with default values.
-    pub fn new() -> Self {
-        Self {
-            prerelease: false,
-            products: Vec::new(),
-            requires: Vec::new(),
-            requires_any: false,
-            version: None,
-            latest: false,
+impl Display for ParseFourPointVersionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let component = |index| self.input.split('.').nth(index).unwrap_or_default();
+        write!(f, "invalid version number `{}`: ", self.input)?;
+        match self.kind {
+            ParseFourPointVersionErrorKind::Length(len) => write!(
+                f,
+                "expected one to four integers separated by a period (`.`), found {len}"
+            ),
+            ParseFourPointVersionErrorKind::Invalid(index) => {
+                write!(f, "`{}` is not an unsigned integer", component(index))
+            }
+            ParseFourPointVersionErrorKind::Overflow(index) => write!(
+                f,
+                "`{}` is greater than the maximum value of {}",
+                component(index),
+                u16::MAX
+            ),
         }
     }
+}
 
-    /// Specifies whether to include pre-release versions of Visual Studio in search results.
+impl error::Error for ParseFourPointVersionError {}
+
+#[allow(clippy::must_use_candidate)]
+impl ProductLine {
+    /// Returns the product line that corresponds to a given year, if any.
     ///
-    /// By default this is `false`.
-    pub fn find_prerelease_versions(&mut self, prerelease: bool) -> &mut Self {
-        self.prerelease = prerelease;
-        self
+    /// For example, `ProductLine::from_year(2019)` returns `Some(ProductLine::Vs2019)`.
+    pub fn from_year(year: u16) -> Option<Self> {
+        match year {
+            2017 => Some(ProductLine::Vs2017),
+            2019 => Some(ProductLine::Vs2019),
+            2022 => Some(ProductLine::Vs2022),
+            2026 => Some(ProductLine::Vs2026),
+            _ => None,
+        }
     }
 
-    /// Adds a string to the product ID (Visual Studio edition) whitelist.
-    ///
-    /// A list of valid product and component IDs is maintained
-    /// [here](https://docs.microsoft.com/en-us/visualstudio/install/workload-and-component-ids).
-    ///
-    /// By default the product ID whitelist is empty, which is equivalent to passing `-products *`
-    /// to vswhere (retrieves information about every installed product, as opposed to just
-    /// Community, Professional and Enterprise). If the product ID whitelist is non-empty, versions
-    /// of Visual Studio without a matching product ID are excluded from search results.
-    pub fn whitelist_product_id<T: ToString + ?Sized>(&mut self, product_id: &T) -> &mut Self {
-        self.products.push(product_id.to_string());
-        self
+    /// Returns the year that a product line is named after.
+    pub fn year(self) -> u16 {
+        match self {
+            ProductLine::Vs2017 => 2017,
+            ProductLine::Vs2019 => 2019,
+            ProductLine::Vs2022 => 2022,
+            ProductLine::Vs2026 => 2026,
+        }
     }
 
-    /// Adds a string to the component ID whitelist.
-    ///
-    /// A list of valid product and component IDs is maintained
-    /// [here](https://docs.microsoft.com/en-us/visualstudio/install/workload-and-component-ids).
-    ///
-    /// By default the component ID whitelist is empty, in which case it is not used. If the
-    /// component ID whitelist is non-empty, versions of Visual Studio are excluded from search
-    /// results based on the filtering method in use (see `Config::require_any_component` for
-    /// more information).
-    pub fn whitelist_component_id<T: ToString + ?Sized>(&mut self, component_id: &T) -> &mut Self {
-        self.requires.push(component_id.to_string());
-        self
+    /// Returns the major version number shared by every release of a product line.
+    pub fn major_version(self) -> u16 {
+        match self {
+            ProductLine::Vs2017 => 15,
+            ProductLine::Vs2019 => 16,
+            ProductLine::Vs2022 => 17,
+            ProductLine::Vs2026 => 18,
+        }
     }
 
-    /// Specifies the method to use for component ID filtering.
+    /// Returns the range of version numbers covered by a product line.
     ///
-    /// If `true`, Visual Studio versions are excluded from search results if they do not provide
+    /// The range starts at `major.0.0.0` and ends at (but excludes) the first version number of
+    /// the next major version.
+    pub fn version_range(self) -> Range<FourPointVersion> {
+        let major = self.major_version();
+        FourPointVersion::new(major, 0, 0, 0)..FourPointVersion::new(major + 1, 0, 0, 0)
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl Arch {
+    /// Returns the architecture that this crate was compiled for, i.e. the host architecture
+    /// for build scripts.
+    ///
+    /// Architectures that the Visual C++ tools do not run on are treated as 32-bit x86.
+    pub fn current() -> Self {
+        if cfg!(target_arch = "x86_64") {
+            Arch::X64
+        } else if cfg!(target_arch = "aarch64") {
+            Arch::Arm64
+        } else {
+            Arch::X86
+        }
+    }
+
+    /// Returns the name used for an architecture in the folder layout of the Visual C++ tools
+    /// (e.g. `x64` in `bin\Hostx64\x64`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Arch::X86 => "x86",
+            Arch::X64 => "x64",
+            Arch::Arm => "arm",
+            Arch::Arm64 => "arm64",
+        }
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl Channel {
+    /// Returns the channel that corresponds to a given channel ID, if any.
+    ///
+    /// Channel IDs take the form `VisualStudio.<major version>.<channel>`, optionally followed by
+    /// further qualifiers (e.g. `VisualStudio.17.Release.LTSC.17.8`); the channel name is
+    /// compared without regard to ASCII case.
+    pub fn from_channel_id(channel_id: &str) -> Option<Self> {
+        let name = channel_id.split('.').nth(2)?;
+        [Channel::Release, Channel::Preview, Channel::IntPreview]
+            .iter()
+            .copied()
+            .find(|channel| channel.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the name of a channel as it appears in channel IDs (e.g. `Preview`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Release => "Release",
+            Channel::Preview => "Preview",
+            Channel::IntPreview => "IntPreview",
+        }
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl Edition {
+    const ALL: [Edition; 7] = [
+        Edition::Enterprise,
+        Edition::Professional,
+        Edition::Community,
+        Edition::BuildTools,
+        Edition::TeamExplorer,
+        Edition::TestAgent,
+        Edition::TestController,
+    ];
+
+    /// Returns the edition that corresponds to a given product ID, if any.
+    ///
+    /// Both fully-qualified product IDs (e.g. `Microsoft.VisualStudio.Product.Enterprise`) and
+    /// bare edition names (e.g. `Enterprise`) are accepted, without regard to ASCII case.
+    pub fn from_product_id(product_id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|edition| {
+            let id = edition.product_id();
+            id.eq_ignore_ascii_case(product_id)
+                || id
+                    .rsplit('.')
+                    .next()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(product_id))
+        })
+    }
+
+    /// Returns the fully-qualified product ID for an edition, as expected by vswhere.
+    pub fn product_id(self) -> &'static str {
+        match self {
+            Edition::Enterprise => ids::PRODUCT_ENTERPRISE,
+            Edition::Professional => ids::PRODUCT_PROFESSIONAL,
+            Edition::Community => ids::PRODUCT_COMMUNITY,
+            Edition::BuildTools => ids::PRODUCT_BUILD_TOOLS,
+            Edition::TeamExplorer => ids::PRODUCT_TEAM_EXPLORER,
+            Edition::TestAgent => ids::PRODUCT_TEST_AGENT,
+            Edition::TestController => ids::PRODUCT_TEST_CONTROLLER,
+        }
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl Component {
+    /// Returns the component ID for a component, as expected by vswhere.
+    pub fn id(self) -> &'static str {
+        match self {
+            Component::MSBuild => ids::COMPONENT_MSBUILD,
+            Component::VcToolsX86X64 => ids::COMPONENT_VC_TOOLS_X86_X64,
+            Component::VcToolsArm => ids::COMPONENT_VC_TOOLS_ARM,
+            Component::VcToolsArm64 => ids::COMPONENT_VC_TOOLS_ARM64,
+            Component::VcAtl => ids::COMPONENT_VC_ATL,
+            Component::VcAtlMfc => ids::COMPONENT_VC_ATL_MFC,
+            Component::VcCMake => ids::COMPONENT_VC_CMAKE,
+            Component::VcLlvmClang => ids::COMPONENT_VC_LLVM_CLANG,
+            Component::Windows10Sdk => ids::COMPONENT_WINDOWS_10_SDK,
+            Component::Windows11Sdk22621 => ids::COMPONENT_WINDOWS_11_SDK_22621,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Error::NotFound => write!(f, "vswhere could not be found"),
+            Error::Spawn(ref e) => write!(f, "failed to run vswhere: {e}"),
+            Error::Exit { code, ref stderr } => {
+                write!(f, "vswhere ran unsuccessfully")?;
+                if let Some(code) = code {
+                    write!(f, " (exit code: {code})")?;
+                }
+                let stderr = stderr.trim();
+                if stderr.is_empty() {
+                    Ok(())
+                } else {
+                    write!(f, ": {stderr}")
+                }
+            }
+            Error::TimedOut => write!(f, "vswhere timed out"),
+            Error::Parse(ref e) => write!(f, "vswhere returned invalid output: {e}"),
+            Error::Ambiguous(count) => write!(
+                f,
+                "vswhere found {count} installations where at most one was expected"
+            ),
+            Error::Config(ref e) => write!(f, "invalid vswhere configuration: {e}"),
+            Error::Extract(ref e) => write!(f, "failed to extract vswhere: {e}"),
+            Error::UnknownVersion(ref banner) => write!(
+                f,
+                "could not find a version number in vswhere's output: {}",
+                banner.trim()
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Spawn(ref e) | Error::Extract(ref e) => Some(e),
+            Error::Parse(ref e) => Some(e),
+            Error::Config(ref e) => Some(e),
+            Error::NotFound
+            | Error::Exit { .. }
+            | Error::TimedOut
+            | Error::Ambiguous(_)
+            | Error::UnknownVersion(_) => None,
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::NotFound => io::Error::new(ErrorKind::NotFound, e),
+            Error::Spawn(e) | Error::Extract(e) => e,
+            Error::Exit { .. } | Error::Ambiguous(_) => io::Error::other(e),
+            Error::TimedOut => io::Error::new(ErrorKind::TimedOut, e),
+            Error::Parse(_) | Error::UnknownVersion(_) => io::Error::new(ErrorKind::InvalidData, e),
+            Error::Config(_) => io::Error::new(ErrorKind::InvalidInput, e),
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::InvertedVersionRange(start, end) => write!(
+                f,
+                "the version range lower bound {start} is greater than the upper bound {end}"
+            ),
+            ConfigError::EmptyVersionRange(version) => write!(
+                f,
+                "the version range excludes its only version number {version}"
+            ),
+            ConfigError::UnsupportedFlag(flag, ref min_version) => {
+                write!(f, "`{flag}` requires vswhere {min_version} or newer")
+            }
+            ConfigError::LatestWithPrereleaseOnly => write!(
+                f,
+                "only searching for pre-release versions cannot be combined with `-latest`"
+            ),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+impl Config {
+    /// Creates a new `Config` instance with default values.
+    pub fn new() -> Self {
+        Self {
+            prerelease: PrereleaseFilter::Exclude,
+            products: Vec::new(),
+            requires: Vec::new(),
+            requires_any: false,
+            version: None,
+            all: false,
+            latest: false,
+            sort: false,
+            sort_locally: false,
+            prefer_64bit: false,
+            timeout: None,
+            utf8: true,
+            code_page: None,
+            color: false,
+            raw_args: Vec::new(),
+            env_clear: false,
+            envs: Vec::new(),
+            current_dir: None,
+            runner: None,
+        }
+    }
+
+    /// Creates a new `Config` instance that matches every Visual Studio installation.
+    ///
+    /// This includes pre-release versions and incomplete installations, as well as every
+    /// product (which is already the default). It is intended for tools that report on every
+    /// installation present on a machine. Note that installations of Visual Studio 2015 and
+    /// older are never reported, since they lack most of the information in `InstallInfo`.
+    pub fn everything() -> Self {
+        let mut config = Self::new();
+        let _ = config
+            .prerelease_filter(PrereleaseFilter::Include)
+            .include_incomplete(true);
+        config
+    }
+
+    /// Specifies whether to include pre-release versions of Visual Studio in search results.
+    ///
+    /// By default this is `false`. This is a shorthand for calling `Config::prerelease_filter`
+    /// with either `PrereleaseFilter::Include` or `PrereleaseFilter::Exclude`.
+    pub fn find_prerelease_versions(&mut self, prerelease: bool) -> &mut Self {
+        self.prerelease_filter(if prerelease {
+            PrereleaseFilter::Include
+        } else {
+            PrereleaseFilter::Exclude
+        })
+    }
+
+    /// Specifies how pre-release versions of Visual Studio are treated in search results.
+    ///
+    /// By default this is `PrereleaseFilter::Exclude`.
+    pub fn prerelease_filter(&mut self, filter: PrereleaseFilter) -> &mut Self {
+        self.prerelease = filter;
+        self
+    }
+
+    /// Adds a string to the product ID (Visual Studio edition) whitelist.
+    ///
+    /// A list of valid product and component IDs is maintained
+    /// [here](https://docs.microsoft.com/en-us/visualstudio/install/workload-and-component-ids),
+    /// and constants for commonly used IDs are provided by the `ids` module. vswhere expects
+    /// fully-qualified product IDs, rather than bare edition names such as `Enterprise`; see
+    /// `Config::whitelist_edition` for a less error-prone alternative.
+    ///
+    /// By default the product ID whitelist is empty, which is equivalent to passing `-products *`
+    /// to vswhere (retrieves information about every installed product, as opposed to just
+    /// Community, Professional and Enterprise). If the product ID whitelist is non-empty, versions
+    /// of Visual Studio without a matching product ID are excluded from search results.
+    ///
+    /// Empty or whitespace-only strings are ignored, since passing them to vswhere would silently
+    /// change the meaning of the query.
+    pub fn whitelist_product_id<T: ToString + ?Sized>(&mut self, product_id: &T) -> &mut Self {
+        push_id(&mut self.products, product_id.to_string());
+        self
+    }
+
+    /// Adds several strings to the product ID whitelist.
+    ///
+    /// This is equivalent to calling `Config::whitelist_product_id` for each string in turn, and
+    /// is useful when product IDs are only known at runtime (e.g. read from a file).
+    pub fn whitelist_product_ids<I>(&mut self, product_ids: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        for product_id in product_ids {
+            push_id(&mut self.products, product_id.to_string());
+        }
+        self
+    }
+
+    /// Adds a string to the component ID whitelist.
+    ///
+    /// A list of valid product and component IDs is maintained
+    /// [here](https://docs.microsoft.com/en-us/visualstudio/install/workload-and-component-ids),
+    /// and constants for commonly used IDs are provided by the `ids` module.
+    ///
+    /// By default the component ID whitelist is empty, in which case it is not used. If the
+    /// component ID whitelist is non-empty, versions of Visual Studio are excluded from search
+    /// results based on the filtering method in use (see `Config::require_any_component` for
+    /// more information).
+    ///
+    /// Empty or whitespace-only strings are ignored.
+    pub fn whitelist_component_id<T: ToString + ?Sized>(&mut self, component_id: &T) -> &mut Self {
+        push_id(&mut self.requires, component_id.to_string());
+        self
+    }
+
+    /// Adds several strings to the component ID whitelist.
+    ///
+    /// This is equivalent to calling `Config::whitelist_component_id` for each string in turn.
+    pub fn whitelist_component_ids<I>(&mut self, component_ids: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        for component_id in component_ids {
+            push_id(&mut self.requires, component_id.to_string());
+        }
+        self
+    }
+
+    /// Adds the IDs of the given components to the component ID whitelist.
+    ///
+    /// This is equivalent to calling `Config::whitelist_component_id` with the result of
+    /// `Component::id` for each component in turn.
+    pub fn whitelist_components(&mut self, components: &[Component]) -> &mut Self {
+        self.whitelist_component_ids(components.iter().map(|component| component.id()))
+    }
+
+    /// Specifies the method to use for component ID filtering.
+    ///
+    /// If `true`, Visual Studio versions are excluded from search results if they do not provide
     /// at least one whitelisted component. Otherwise if `false` (the default value), Visual Studio
     /// versions are excluded if they do not provide every whitelisted component.
     ///
     /// This is only meaningful if the component ID whitelist is non-empty, as filtering by
-    /// component ID is disabled otherwise.
+    /// component ID is disabled otherwise. `Config::requires_filter` sets both the whitelist and
+    /// the filtering method at once, and is usually preferable.
     pub fn require_any_component(&mut self, require_any: bool) -> &mut Self {
         self.requires_any = require_any;
         self
     }
 
+    /// Replaces the component ID whitelist and filtering method.
+    ///
+    /// Unlike `Config::whitelist_component_id` and `Config::require_any_component`, this
+    /// specifies the component IDs together with how they are combined, so the two cannot
+    /// become inconsistent. Empty or whitespace-only strings are ignored.
+    pub fn requires_filter(&mut self, filter: RequiresFilter<'_>) -> &mut Self {
+        let (component_ids, require_any) = match filter {
+            RequiresFilter::All(component_ids) => (component_ids, false),
+            RequiresFilter::Any(component_ids) => (component_ids, true),
+        };
+        self.requires.clear();
+        self.require_any_component(require_any)
+            .whitelist_component_ids(component_ids)
+    }
+
     /// Excludes Visual Studio installations whose version number falls outside of a given range.
     ///
-    /// By default no installations are excluded based on version number.
+    /// By default no installations are excluded based on version number. This is equivalent to
+    /// calling `Config::version_range` with a half-open range.
     pub fn version_number_range(&mut self, range: Range<FourPointVersion>) -> &mut Self {
-        self.version = Some(range);
+        self.version_range(range)
+    }
+
+    /// Excludes Visual Studio installations whose version number falls outside of a given range.
+    ///
+    /// Any of Rust's range types may be used (e.g. `a..b`, `a..=b` or `a..`), and each bound is
+    /// passed to vswhere as either inclusive or exclusive accordingly. Passing `..` removes any
+    /// version number range set previously.
+    pub fn version_range<R: RangeBounds<FourPointVersion>>(&mut self, range: R) -> &mut Self {
+        self.version = match (range.start_bound().cloned(), range.end_bound().cloned()) {
+            (Bound::Unbounded, Bound::Unbounded) => None,
+            bounds => Some(bounds),
+        };
+        self
+    }
+
+    /// Excludes Visual Studio installations that do not belong to a given product line.
+    ///
+    /// This is equivalent to calling `Config::version_number_range` with the range returned by
+    /// `ProductLine::version_range`, and so replaces any version number range set previously.
+    pub fn product_line(&mut self, product_line: ProductLine) -> &mut Self {
+        self.version_number_range(product_line.version_range())
+    }
+
+    /// Adds the product ID for the given edition to the product ID whitelist.
+    ///
+    /// This is equivalent to calling `Config::whitelist_product_id` with the result of
+    /// `Edition::product_id`. Note that Build Tools installations are found by default, since the
+    /// whitelist starts out empty; but once any product ID is whitelisted, Build Tools must be
+    /// whitelisted explicitly (via `Edition::BuildTools`) if it should still be found.
+    pub fn whitelist_edition(&mut self, edition: Edition) -> &mut Self {
+        self.whitelist_product_id(edition.product_id())
+    }
+
+    /// If `true`, include incomplete installations of Visual Studio in search results, as well as
+    /// those that cannot be launched.
+    ///
+    /// By default this is `false`. `InstallInfo::is_complete` and related methods can be used to
+    /// distinguish such installations, so that they can be reported but then dropped from
+    /// further consideration (e.g. `instances.into_iter().filter(InstallInfo::is_complete)`).
+    /// `InstallInfo::is_usable` combines each of these checks.
+    pub fn include_incomplete(&mut self, all: bool) -> &mut Self {
+        self.all = all;
         self
     }
 
@@ -352,361 +1690,3221 @@ impl Config {
         self
     }
 
-    /// Invokes a vswhere instance installed in a default location, using the current
-    /// configuration.
-    ///
-    /// This method tries running known vswhere instances, stopping at the first successful
-    /// invocation, in the following order:
-    ///
-    /// 1. `[ProgramData]\chocolatey\bin\vswhere.exe`
-    /// 2. `[ProgramFilesX86]\Microsoft Visual Studio\Installer\vswhere.exe`
-    ///
-    /// Note that `[ProgramData]` and `[ProgramFilesX86]` correspond to paths returned from the
-    /// Windows API function `SHGetKnownFolderPath`.
-    pub fn run_default_path(&self) -> io::Result<Vec<InstallInfo>> {
-        use winapi::ctypes::c_void;
-        use winapi::shared::ntdef::PWSTR;
-        use winapi::shared::winerror::S_OK;
-        use winapi::um::combaseapi::CoTaskMemFree;
-        use winapi::um::knownfolders::{FOLDERID_ProgramData, FOLDERID_ProgramFilesX86};
-        use winapi::um::shlobj::SHGetKnownFolderPath;
-        use winapi::um::shtypes::REFKNOWNFOLDERID;
-
-        fn get_known_folder_path(id: REFKNOWNFOLDERID) -> io::Result<PathBuf> {
-            struct KnownFolderPath(PWSTR);
-
-            impl Drop for KnownFolderPath {
-                fn drop(&mut self) {
-                    unsafe {
-                        CoTaskMemFree(self.0 as *mut c_void);
-                    }
-                }
-            }
+    /// If `true`, sort search results from newest version and most recently installed to oldest.
+    ///
+    /// By default this is `false`, in which case the order of search results is unspecified and
+    /// may differ between machines. When combined with `Config::only_latest_versions`, the
+    /// installations that remain are sorted in the same way.
+    pub fn sort_newest_first(&mut self, sort: bool) -> &mut Self {
+        self.sort = sort;
+        self
+    }
+
+    /// If `true`, sort search results from newest version and most recently installed to oldest
+    /// after they are returned by vswhere, rather than asking vswhere to sort them.
+    ///
+    /// By default this is `false`. Unlike `Config::sort_newest_first`, this works with versions
+    /// of vswhere that do not support `-sort`, and the order does not depend on vswhere's own
+    /// sorting rules. It only affects methods that return `InstallInfo`; the output of
+    /// `Config::run_custom_path_as` and similar methods is left as-is.
+    pub fn sort_newest_first_locally(&mut self, sort: bool) -> &mut Self {
+        self.sort_locally = sort;
+        self
+    }
+
+    /// If `true`, prefer 64-bit tools over 32-bit ones when searching for tools that are
+    /// installed in both forms, such as via `Config::find_msbuild_custom_path`.
+    ///
+    /// By default this is `false`, in which case the 32-bit tools are returned, matching the
+    /// behaviour of the Visual Studio developer command prompt. 32-bit tools are still returned
+    /// if no 64-bit version is found.
+    pub fn prefer_64bit_tools(&mut self, prefer_64bit: bool) -> &mut Self {
+        self.prefer_64bit = prefer_64bit;
+        self
+    }
+
+    /// Specifies the maximum amount of time to wait for vswhere to finish running.
+    ///
+    /// If vswhere is still running once the timeout has elapsed, it is terminated and
+    /// `Error::TimedOut` is returned. By default this is `None`, in which case vswhere is allowed
+    /// to run indefinitely. Only vswhere itself is terminated: any processes it started (e.g. if
+    /// vswhere is a batch file wrapper) keep running until they exit on their own.
+    pub fn timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Specifies whether to pass `-utf8` to vswhere, forcing it to generate UTF-8 encoded output.
+    ///
+    /// By default this is `true`. vswhere versions older than 2.5.2 do not recognise `-utf8` and
+    /// exit unsuccessfully if it is passed, so this may be disabled in order to use them. In that
+    /// case vswhere generates output in the system default (ANSI) code page instead, which is
+    /// converted to UTF-8 before being parsed; see `Config::code_page` to use a different code
+    /// page.
+    pub fn utf8(&mut self, utf8: bool) -> &mut Self {
+        self.utf8 = utf8;
+        self
+    }
+
+    /// Specifies the code page used to decode the output of vswhere when `Config::utf8` is
+    /// disabled.
+    ///
+    /// By default this is `None`, in which case the system default (ANSI) code page is used.
+    /// Some builds of vswhere instead generate output in another code page, such as the console
+    /// output code page reported by `chcp`; specifying it here ensures that installation paths
+    /// containing non-ASCII characters are decoded correctly. This has no effect when
+    /// `Config::utf8` is enabled, or on platforms other than Windows.
+    pub fn code_page(&mut self, code_page: Option<u32>) -> &mut Self {
+        self.code_page = code_page;
+        self
+    }
+
+    /// Specifies whether vswhere may colorize text output returned by `Config::run_raw_*`.
+    ///
+    /// By default this is `false`, in which case `-nocolor` is passed whenever the output format
+    /// is `OutputFormat::Text` or `OutputFormat::Value`, so that captured output is free of ANSI
+    /// escape codes. It has no effect on JSON or XML output, which is never colorized.
+    pub fn color(&mut self, color: bool) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    /// Adds an argument to be passed to vswhere verbatim.
+    ///
+    /// This allows making use of vswhere features that are not otherwise supported by `Config`.
+    /// Arguments added in this way are passed after all other arguments, in the order in which
+    /// they were added. Note that vswhere must still produce JSON output that can be understood
+    /// by the method used to run it.
+    pub fn raw_arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.raw_args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    /// Specifies whether vswhere should be run without inheriting any environment variables.
+    ///
+    /// By default this is `false`. Variables set via `Config::env` are still passed to vswhere
+    /// when this is enabled. Note that some Windows components rely on variables such as
+    /// `SystemRoot` being present, so these may need to be set explicitly.
+    pub fn env_clear(&mut self, clear: bool) -> &mut Self {
+        self.env_clear = clear;
+        self
+    }
+
+    /// Sets an environment variable for vswhere.
+    ///
+    /// Environment variables set by Visual Studio developer prompts (e.g. `VSINSTALLDIR`) can
+    /// affect the behaviour of vswhere; this and `Config::env_remove` allow overriding them so
+    /// that search results do not depend on the environment of the calling process.
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, value: V) -> &mut Self {
+        self.envs
+            .push((key.as_ref().to_owned(), Some(value.as_ref().to_owned())));
+        self
+    }
+
+    /// Prevents vswhere from inheriting an environment variable.
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Self {
+        self.envs.push((key.as_ref().to_owned(), None));
+        self
+    }
+
+    /// Specifies the working directory to run vswhere in.
+    ///
+    /// By default vswhere inherits the working directory of the calling process.
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.current_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Specifies the `Runner` used to run vswhere.
+    ///
+    /// By default `ProcessRunner` is used. Supplying a different runner (e.g. `MockRunner`)
+    /// allows code that depends on vswhere to be tested without Visual Studio being installed;
+    /// in such tests, prefer the `run_custom_path` family of methods, since searching default
+    /// locations is only supported on Windows. Asynchronous methods call the runner on tokio's
+    /// blocking thread pool. The runner is not serialized.
+    pub fn runner<R: Runner + 'static>(&mut self, runner: R) -> &mut Self {
+        self.runner = Some(Arc::new(runner));
+        self
+    }
+
+    /// Returns the arguments that would be passed to vswhere by `Config::run_custom_path`.
+    ///
+    /// This allows inspecting the effect of a configuration without running vswhere, e.g. when
+    /// testing code that builds a `Config`. Other run methods may append further arguments (for
+    /// example, `-property` or `-find`).
+    pub fn args(&self) -> Vec<OsString> {
+        self.command("vswhere.exe")
+            .get_args()
+            .map(OsStr::to_owned)
+            .collect()
+    }
+
+    /// Returns the command line that `Config::run_custom_path` would use to run vswhere at the
+    /// specified path.
+    ///
+    /// The result is a `CreateProcess` command line: the path is quoted according to the rules
+    /// that `CreateProcess` uses to find the program name, and the arguments according to the
+    /// rules used by the Microsoft C runtime. This is useful for logging or reproducing a query,
+    /// but characters that are special to `cmd.exe` (such as `&` or `%`) are not escaped, so it
+    /// should not be passed to a shell as-is. Any arguments that are not valid Unicode are
+    /// converted lossily.
+    pub fn command_line<P: AsRef<Path>>(&self, path: P) -> String {
+        let mut command_line = String::new();
+        quote_program_name(&path.as_ref().to_string_lossy(), &mut command_line);
+        for arg in self.args() {
+            command_line.push(' ');
+            quote_windows_arg(&arg.to_string_lossy(), &mut command_line);
+        }
+        command_line
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration.
+    ///
+    /// This method tries running known vswhere instances, stopping at the first successful
+    /// invocation, in the following order:
+    ///
+    /// 1. `[ProgramData]\chocolatey\bin\vswhere.exe`
+    /// 2. `[ProgramFilesX86]\Microsoft Visual Studio\Installer\vswhere.exe`
+    /// 3. `[ProgramFiles]\Microsoft Visual Studio\Installer\vswhere.exe`
+    ///
+    /// Note that `[ProgramData]`, `[ProgramFilesX86]` and `[ProgramFiles]` correspond to the
+    /// `ProgramData`, `ProgramFiles(x86)` and `ProgramFiles` environment variables respectively,
+    /// or to paths returned from the Windows API function `SHGetKnownFolderPath` if those
+    /// variables are unset. Locations whose known folder cannot be retrieved, or that name a
+    /// directory rather than a file, are skipped, and `Error::NotFound` is only returned once
+    /// every location has been tried.
+    ///
+    /// A location is also skipped if the file there cannot be run at all, e.g. because access to
+    /// it is denied or it is not a valid executable; if no other location succeeds, the error
+    /// from the last such location is returned as `Error::Spawn`. Any other error, including
+    /// vswhere running unsuccessfully, stops the search immediately.
+    ///
+    /// If the `VSWHERE_PATH` environment variable is set to a non-empty value, it is used as the
+    /// full path to a vswhere executable instead, and no other locations are tried. In this case
+    /// `Error::NotFound` is returned if no executable exists at that path.
+    ///
+    /// To guarantee that `SHGetKnownFolderPath` is never called (e.g. in locked-down
+    /// environments), either set `VSWHERE_PATH`, or use `Config::run_custom_path` or
+    /// `VsWhere::from_path` with an explicit path; neither of these search default locations.
+    pub fn run_default_path(&self) -> Result<Vec<InstallInfo>, Error> {
+        with_default_path(|path| self.run_custom_path(path))
+    }
+
+    /// Invokes the first vswhere instance found in the specified directories, using the current
+    /// configuration.
+    ///
+    /// This is equivalent to calling `VsWhere::locate_in` followed by `VsWhere::run`.
+    pub fn run_in_dirs<I>(&self, dirs: I) -> Result<Vec<InstallInfo>, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        VsWhere::locate_in(dirs).and_then(|vswhere| vswhere.run(self))
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration.
+    ///
+    /// The specified path must point to an executable, rather than a folder.
+    pub fn run_custom_path<P: AsRef<Path>>(&self, path: P) -> Result<Vec<InstallInfo>, Error> {
+        self.run_custom_path_as(path)
+            .map(|instances| filter_instances(self.prerelease, self.sort_locally, instances))
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration, and returns its exit status alongside the matching installations.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_with_status` for more information.
+    pub fn run_default_path_with_status(&self) -> Result<(Vec<InstallInfo>, ExitStatus), Error> {
+        with_default_path(|path| self.run_custom_path_with_status(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration, and
+    /// returns its exit status alongside the matching installations.
+    ///
+    /// This behaves identically to `Config::run_custom_path`, and so still returns `Error::Exit`
+    /// if vswhere exits unsuccessfully; the exit status is returned so that callers can inspect
+    /// it even when vswhere succeeds.
+    pub fn run_custom_path_with_status<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Vec<InstallInfo>, ExitStatus), Error> {
+        let output = self.output(&mut self.command(path))?;
+        parse_output(&output, self.output_code_page()).map(|instances| {
+            (
+                filter_instances(self.prerelease, self.sort_locally, instances),
+                output.status,
+            )
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration, and returns any warnings it printed alongside the matching installations.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_with_warnings` for more information.
+    pub fn run_default_path_with_warnings(&self) -> Result<(Vec<InstallInfo>, Vec<String>), Error> {
+        with_default_path(|path| self.run_custom_path_with_warnings(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration, and
+    /// returns any warnings it printed alongside the matching installations.
+    ///
+    /// vswhere may write warnings (such as notices about deprecated flags) to standard error
+    /// while still running successfully; these are otherwise discarded. Each non-empty line of
+    /// standard error is returned as a separate warning. If vswhere runs unsuccessfully,
+    /// `Error::Exit` is returned as usual, with standard error included.
+    pub fn run_custom_path_with_warnings<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Vec<InstallInfo>, Vec<String>), Error> {
+        let output = self.output(&mut self.command(path))?;
+        let code_page = self.output_code_page();
+        parse_output(&output, code_page).map(|instances| {
+            let warnings = decode_stdout(&output.stderr, code_page)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned)
+                .collect();
+            (
+                filter_instances(self.prerelease, self.sort_locally, instances),
+                warnings,
+            )
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration, and returns its raw JSON output alongside the matching installations.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_with_raw` for more information.
+    pub fn run_default_path_with_raw(&self) -> Result<(Vec<InstallInfo>, String), Error> {
+        with_default_path(|path| self.run_custom_path_with_raw(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration, and
+    /// returns its raw JSON output alongside the matching installations.
+    ///
+    /// This is useful for logging the output of vswhere, or hashing it to produce a cache key,
+    /// without running it a second time. The raw output is returned exactly as vswhere produced
+    /// it (after conversion to UTF-8), and so is not affected by `PrereleaseFilter::Only`.
+    pub fn run_custom_path_with_raw<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Vec<InstallInfo>, String), Error> {
+        let output = self.output(&mut self.command(path))?;
+        let raw = decode_stdout(successful_stdout(&output)?, self.output_code_page()).into_owned();
+        parse_instances_as(raw.as_bytes()).map(|instances| {
+            (
+                filter_instances(self.prerelease, self.sort_locally, instances),
+                raw,
+            )
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration, and returns an iterator over the matching installations.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched.
+    pub fn run_iter_default_path(&self) -> Result<impl Iterator<Item = InstallInfo>, Error> {
+        self.run_default_path().map(IntoIterator::into_iter)
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration, and
+    /// returns an iterator over the matching installations.
+    ///
+    /// vswhere reports every installation at once, so this is equivalent to iterating over the
+    /// result of `Config::run_custom_path`, but composes more easily with iterator adaptors
+    /// (e.g. `config.run_iter_default_path()?.find(InstallInfo::is_complete)`).
+    pub fn run_iter_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<impl Iterator<Item = InstallInfo>, Error> {
+        self.run_custom_path(path).map(IntoIterator::into_iter)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving information about
+    /// the Visual Studio installation with the given instance ID.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_by_instance_id` for details on how the installation is selected.
+    pub fn run_default_path_by_instance_id(
+        &self,
+        instance_id: &str,
+    ) -> Result<Option<InstallInfo>, Error> {
+        with_default_path(|path| self.run_custom_path_by_instance_id(path, instance_id))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving information about the Visual
+    /// Studio installation with the given instance ID.
+    ///
+    /// vswhere cannot search for installations by instance ID, so every installation that
+    /// matches the current configuration is retrieved and then searched in turn. As such, `None`
+    /// is returned if the installation exists but is excluded by the current configuration; in
+    /// particular, `Config::only_latest_versions` should usually be disabled.
+    pub fn run_custom_path_by_instance_id<P: AsRef<Path>>(
+        &self,
+        path: P,
+        instance_id: &str,
+    ) -> Result<Option<InstallInfo>, Error> {
+        self.run_custom_path(path).map(|instances| {
+            instances
+                .into_iter()
+                .find(|instance| instance.instance_id() == instance_id)
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving information about
+    /// the Visual Studio installation that contains the given path.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_by_location` for details on how installations are matched.
+    pub fn run_default_path_by_location<D: AsRef<Path>>(
+        &self,
+        location: D,
+    ) -> Result<Option<InstallInfo>, Error> {
+        with_default_path(|path| self.run_custom_path_by_location(path, location.as_ref()))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving information about the Visual
+    /// Studio installation that contains the given path.
+    ///
+    /// The given path may be the installation path itself, or any path beneath it; components
+    /// are compared without regard to ASCII case, as on Windows. vswhere's own `-path` flag cannot
+    /// be combined with other filters, so every installation that matches the current
+    /// configuration is retrieved and then searched in turn. This allows asking for e.g. the
+    /// installation containing a directory, but only if it is a pre-release version.
+    pub fn run_custom_path_by_location<P: AsRef<Path>, D: AsRef<Path>>(
+        &self,
+        path: P,
+        location: D,
+    ) -> Result<Option<InstallInfo>, Error> {
+        self.run_custom_path(path).map(|instances| {
+            instances
+                .into_iter()
+                .find(|instance| path_contains(instance.installation_path(), location.as_ref()))
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving information about
+    /// the only Visual Studio installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_one_custom_path` for more information.
+    pub fn run_one_default_path(&self) -> Result<Option<InstallInfo>, Error> {
+        with_default_path(|path| self.run_one_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving information about the only
+    /// Visual Studio installation that matches the current configuration.
+    ///
+    /// `None` is returned if no installations match, while `Error::Ambiguous` is returned if
+    /// more than one installation matches. Enabling `Config::only_latest_versions` guarantees
+    /// that at most one installation is found.
+    pub fn run_one_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<InstallInfo>, Error> {
+        self.run_custom_path(path).and_then(single_instance)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving information about
+    /// the newest Visual Studio installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_latest_custom_path` for more information.
+    pub fn run_latest_default_path(&self) -> Result<Option<InstallInfo>, Error> {
+        with_default_path(|path| self.run_latest_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving information about the newest
+    /// Visual Studio installation that matches the current configuration.
+    ///
+    /// This is equivalent to calling `Config::run_one_custom_path` with
+    /// `Config::only_latest_versions` enabled. `None` is returned if no installations match, and
+    /// `Error::Ambiguous` is returned if vswhere unexpectedly reports more than one.
+    ///
+    /// If `PrereleaseFilter::Only` is in use, every matching installation is retrieved instead,
+    /// and the newest and most recently installed pre-release version is returned.
+    pub fn run_latest_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<InstallInfo>, Error> {
+        let mut config = self.clone();
+        if self.prerelease == PrereleaseFilter::Only {
+            let _ = config.only_latest_versions(false);
+            return config.run_custom_path(path).map(|instances| {
+                instances
+                    .into_iter()
+                    .max_by_key(|instance| (instance.installation_version, instance.install_date))
+            });
+        }
+        let _ = config.only_latest_versions(true);
+        config.run_one_custom_path(path)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, using the current
+    /// configuration, and deserializes its output into a caller-provided type.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_custom_path_as` for details on how output is deserialized.
+    pub fn run_default_path_as<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        with_default_path(|path| self.run_custom_path_as(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, using the current configuration, and
+    /// deserializes its output into a caller-provided type.
+    ///
+    /// vswhere outputs a JSON array containing one object per installation, using camel-cased
+    /// field names (e.g. `installationPath`). This method allows deserializing only the fields of
+    /// interest, or deserializing into a `serde_json::Value` to inspect the output as-is.
+    pub fn run_custom_path_as<T: DeserializeOwned, P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<T, Error> {
+        self.run_json(&mut self.command(path))
+    }
+
+    /// Invokes a vswhere instance installed in a default location, returning the number of
+    /// Visual Studio installations that match the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::count_custom_path` for more information.
+    pub fn count_default_path(&self) -> Result<usize, Error> {
+        with_default_path(|path| self.count_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, returning the number of Visual Studio
+    /// installations that match the current configuration.
+    ///
+    /// This is cheaper than calling `Config::run_custom_path` and measuring the result, since
+    /// the details of each installation are skipped over rather than deserialized.
+    pub fn count_custom_path<P: AsRef<Path>>(&self, path: P) -> Result<usize, Error> {
+        if self.prerelease == PrereleaseFilter::Only {
+            self.run_custom_path(path).map(|instances| instances.len())
+        } else {
+            self.run_custom_path_as(path)
+                .map(|instances: Vec<IgnoredAny>| instances.len())
+        }
+    }
+
+    /// Invokes a vswhere instance installed in a default location, returning whether any Visual
+    /// Studio installation matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched.
+    pub fn any_default_path(&self) -> Result<bool, Error> {
+        self.count_default_path().map(|count| count != 0)
+    }
+
+    /// Invokes a vswhere instance at the specified path, returning whether any Visual Studio
+    /// installation matches the current configuration.
+    pub fn any_custom_path<P: AsRef<Path>>(&self, path: P) -> Result<bool, Error> {
+        self.count_custom_path(path).map(|count| count != 0)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, returning whether any Visual
+    /// Studio installation that matches the current configuration provides a given component.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::has_component_custom_path` for more information.
+    pub fn has_component_default_path(&self, component_id: &str) -> Result<bool, Error> {
+        with_default_path(|path| self.has_component_custom_path(path, component_id))
+    }
+
+    /// Invokes a vswhere instance at the specified path, returning whether any Visual Studio
+    /// installation that matches the current configuration provides a given component.
+    ///
+    /// Any component IDs already whitelisted are replaced by the given component ID for this
+    /// query. Since vswhere applies `-requires` before `-latest`, if `Config::only_latest_versions`
+    /// is enabled the newest matching installation is resolved first and then queried again for
+    /// the component, so that this method answers whether that installation provides it.
+    /// Constants for commonly used IDs are provided by the `ids` module and `Component::id`.
+    pub fn has_component_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        component_id: &str,
+    ) -> Result<bool, Error> {
+        let query = self.component_query(component_id);
+        if !self.latest {
+            return query.any_custom_path(path);
+        }
+        match self.run_latest_custom_path(&path)? {
+            Some(latest) => query
+                .run_custom_path_by_instance_id(path, latest.instance_id())
+                .map(|instance| instance.is_some()),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns a copy of this configuration that only matches installations providing the given
+    /// component, for use by `Config::has_component_custom_path`.
+    fn component_query(&self, component_id: &str) -> Self {
+        let mut query = self.clone();
+        let _ = query
+            .requires_filter(RequiresFilter::All(&[component_id]))
+            .only_latest_versions(false);
+        query
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving the value of a
+    /// single property for each Visual Studio installation that matches the current
+    /// configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_property_custom_path` for details on how property values are returned.
+    pub fn run_property_default_path(&self, name: &str) -> Result<Vec<String>, Error> {
+        with_default_path(|path| self.run_property_custom_path(path, name))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving the value of a single
+    /// property for each Visual Studio installation that matches the current configuration.
+    ///
+    /// The property name is passed to vswhere verbatim, so nested properties may be specified
+    /// using a period as a separator (e.g. `catalog.productDisplayVersion`). String values are
+    /// returned as-is, while other values are returned in their JSON representation. If a
+    /// property is not present for an installation, an empty string is returned in its place.
+    pub fn run_property_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+    ) -> Result<Vec<String>, Error> {
+        let mut cmd = self.command(path);
+        let _ = cmd.args(["-property", name]);
+        self.run_json(&mut cmd).map(property_values)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving the value of a
+    /// single property for each Visual Studio installation that matches the current
+    /// configuration, without using JSON output.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_property_values_custom_path` for details on how property values are returned.
+    pub fn run_property_values_default_path(&self, name: &str) -> Result<Vec<String>, Error> {
+        with_default_path(|path| self.run_property_values_custom_path(path, name))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving the value of a single
+    /// property for each Visual Studio installation that matches the current configuration,
+    /// without using JSON output.
+    ///
+    /// This passes `-format value` to vswhere, which prints each value on its own line, and so
+    /// avoids parsing JSON entirely. Unlike `Config::run_property_custom_path`, every value is
+    /// returned exactly as printed by vswhere. Empty values are preserved as empty strings, so
+    /// that the returned values remain in the same order as the matching installations.
+    pub fn run_property_values_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+    ) -> Result<Vec<String>, Error> {
+        let mut cmd = self.command_with_format(path, OutputFormat::Value);
+        let _ = cmd.args(["-property", name]);
+        self.output(&mut cmd).and_then(|output| {
+            successful_stdout(&output).map(|stdout| {
+                decode_stdout(stdout, self.output_code_page())
+                    .lines()
+                    .map(str::to_owned)
+                    .collect()
+            })
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, searching for files within
+    /// each Visual Studio installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_find_custom_path` for details on how the search is performed.
+    pub fn run_find_default_path(&self, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+        with_default_path(|path| self.run_find_custom_path(path, pattern))
+    }
+
+    /// Invokes a vswhere instance at the specified path, searching for files within each Visual
+    /// Studio installation that matches the current configuration.
+    ///
+    /// The pattern is relative to the root of each installation and is passed to vswhere
+    /// verbatim; `*` matches any part of a file or folder name, while `**` matches any number of
+    /// folders (e.g. `VC\Tools\MSVC\**\cl.exe`). The full paths of all matching files are
+    /// returned. Note that older versions of vswhere do not support searching for files.
+    pub fn run_find_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pattern: &str,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let mut cmd = self.command(path);
+        let _ = cmd.args(["-find", pattern]);
+        self.run_json(&mut cmd)
+    }
+
+    /// Invokes a vswhere instance installed in a default location, searching for the MSBuild
+    /// executable within the latest Visual Studio installation that matches the current
+    /// configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::find_msbuild_custom_path` for details on how MSBuild is located.
+    pub fn find_msbuild_default_path(&self) -> Result<Option<PathBuf>, Error> {
+        with_default_path(|path| self.find_msbuild_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, searching for the MSBuild executable
+    /// within the latest Visual Studio installation that matches the current configuration.
+    ///
+    /// This is equivalent to calling `Config::run_find_custom_path` with the pattern
+    /// `MSBuild\**\Bin\MSBuild.exe`, with `Config::only_latest_versions` enabled. If several
+    /// versions of MSBuild are found, the one located in the `Current` folder (used by Visual
+    /// Studio 2019 and newer) is preferred over versioned folders such as `15.0`. `None` is
+    /// returned if MSBuild could not be found.
+    ///
+    /// If `Config::prefer_64bit_tools` is enabled, the pattern `MSBuild\**\Bin\**\MSBuild.exe`
+    /// is used instead, and the 64-bit MSBuild in `Bin\amd64` is preferred over the 32-bit one
+    /// from the same version of MSBuild.
+    pub fn find_msbuild_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<PathBuf>, Error> {
+        fn name_is(path: Option<&Path>, name: &str) -> bool {
+            path.and_then(Path::file_name)
+                .is_some_and(|file_name| file_name.eq_ignore_ascii_case(name))
+        }
+
+        let mut config = self.clone();
+        let _ = config.only_latest_versions(true);
+        let pattern = if self.prefer_64bit {
+            r"MSBuild\**\Bin\**\MSBuild.exe"
+        } else {
+            r"MSBuild\**\Bin\MSBuild.exe"
+        };
+        config.run_find_custom_path(path, pattern).map(|paths| {
+            let rank = |p: &PathBuf| {
+                let is_64bit = name_is(p.parent(), "amd64");
+                let bin = if is_64bit {
+                    p.parent().and_then(Path::parent)
+                } else {
+                    p.parent()
+                };
+                let is_current = name_is(bin.and_then(Path::parent), "Current");
+                (is_current, is_64bit == self.prefer_64bit)
+            };
+            let best = paths
+                .iter()
+                .enumerate()
+                .max_by_key(|&(index, p)| (rank(p), cmp::Reverse(index)))
+                .map(|(index, _)| index);
+            paths.into_iter().nth(best.unwrap_or(0))
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, searching for `vcvarsall.bat`
+    /// within the latest Visual Studio installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::find_vcvarsall_custom_path` for details on how `vcvarsall.bat` is located.
+    pub fn find_vcvarsall_default_path(&self) -> Result<Option<PathBuf>, Error> {
+        with_default_path(|path| self.find_vcvarsall_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, searching for `vcvarsall.bat` within the
+    /// latest Visual Studio installation that matches the current configuration.
+    ///
+    /// This is equivalent to calling `Config::run_find_custom_path` with the pattern
+    /// `VC\Auxiliary\Build\vcvarsall.bat`, with `Config::only_latest_versions` enabled. `None`
+    /// is returned if the installation does not include the C++ build tools. Consider also
+    /// whitelisting `Component::VcToolsX86X64` (or a similar component) so that installations
+    /// without them are skipped.
+    pub fn find_vcvarsall_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<PathBuf>, Error> {
+        self.find_latest_custom_path(path, r"VC\Auxiliary\Build\vcvarsall.bat")
+    }
+
+    /// Invokes a vswhere instance installed in a default location, searching for `VsDevCmd.bat`
+    /// within the latest Visual Studio installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::find_vsdevcmd_custom_path` for details on how `VsDevCmd.bat` is located.
+    pub fn find_vsdevcmd_default_path(&self) -> Result<Option<PathBuf>, Error> {
+        with_default_path(|path| self.find_vsdevcmd_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, searching for `VsDevCmd.bat` within the
+    /// latest Visual Studio installation that matches the current configuration.
+    ///
+    /// This is equivalent to calling `Config::run_find_custom_path` with the pattern
+    /// `Common7\Tools\VsDevCmd.bat`, with `Config::only_latest_versions` enabled. `None` is
+    /// returned if `VsDevCmd.bat` could not be found.
+    pub fn find_vsdevcmd_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<PathBuf>, Error> {
+        self.find_latest_custom_path(path, r"Common7\Tools\VsDevCmd.bat")
+    }
+
+    /// Invokes a vswhere instance installed in a default location, resolving the tools needed to
+    /// build native code from the newest usable Visual Studio installation that matches the
+    /// current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::find_vs_tools_custom_path` for more information.
+    pub fn find_vs_tools_default_path(&self) -> Result<Option<VsTools>, Error> {
+        with_default_path(|path| self.find_vs_tools_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, resolving the tools needed to build
+    /// native code from the newest usable Visual Studio installation that matches the current
+    /// configuration.
+    ///
+    /// vswhere is only run once; the installation with the highest version number for which
+    /// `InstallInfo::is_usable` returns `true` is chosen, and its tools are then located on
+    /// disk. `None` is returned if no usable installation matches. Consider also whitelisting
+    /// `Component::VcToolsX86X64` (or a similar component) so that installations without the
+    /// C++ build tools are skipped.
+    pub fn find_vs_tools_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<VsTools>, Error> {
+        self.run_custom_path(path).map(|instances| {
+            instances
+                .into_iter()
+                .filter(InstallInfo::is_usable)
+                .max_by_key(|instance| instance.installation_version)
+                .map(|instance| VsTools::resolve(instance, self.prefer_64bit))
+        })
+    }
+
+    /// Invokes a vswhere instance installed in a default location, searching for the `cl.exe`
+    /// that runs on and targets the given architectures within the latest Visual Studio
+    /// installation that matches the current configuration.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::find_cl_custom_path` for details on how `cl.exe` is located.
+    pub fn find_cl_default_path(&self, host: Arch, target: Arch) -> Result<Option<PathBuf>, Error> {
+        with_default_path(|path| self.find_cl_custom_path(path, host, target))
+    }
+
+    /// Invokes a vswhere instance at the specified path, searching for the `cl.exe` that runs on
+    /// and targets the given architectures within the latest Visual Studio installation that
+    /// matches the current configuration.
+    ///
+    /// This is equivalent to calling `Config::run_find_custom_path` with the pattern
+    /// `VC\Tools\MSVC\*\bin\Host<host>\<target>\cl.exe`, with
+    /// `Config::only_latest_versions` enabled. If several MSVC toolsets are installed side by
+    /// side, the `cl.exe` from the newest one is returned. `None` is returned if the
+    /// installation has no toolset for the given host and target, e.g. because the matching
+    /// cross-compilation component is not installed.
+    pub fn find_cl_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        host: Arch,
+        target: Arch,
+    ) -> Result<Option<PathBuf>, Error> {
+        let pattern = format!(
+            r"VC\Tools\MSVC\*\bin\Host{}\{}\cl.exe",
+            host.name(),
+            target.name()
+        );
+        let mut config = self.clone();
+        let _ = config.only_latest_versions(true);
+        config.run_find_custom_path(path, &pattern).map(|paths| {
+            // `VC\Tools\MSVC\<toolset>\bin\Host<host>\<target>\cl.exe`
+            let toolset = |path: &PathBuf| {
+                path.ancestors()
+                    .nth(4)
+                    .and_then(Path::file_name)
+                    .and_then(OsStr::to_str)
+                    .and_then(|name| name.parse::<FourPointVersion>().ok())
+            };
+            paths.into_iter().max_by_key(toolset)
+        })
+    }
+
+    fn find_latest_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pattern: &str,
+    ) -> Result<Option<PathBuf>, Error> {
+        let mut config = self.clone();
+        let _ = config.only_latest_versions(true);
+        config
+            .run_find_custom_path(path, pattern)
+            .map(|paths| paths.into_iter().next())
+    }
+
+    /// Invokes a vswhere instance installed in a default location, returning its output in the
+    /// specified format without parsing it.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::run_raw_custom_path` for details on how the output is returned.
+    pub fn run_raw_default_path(&self, format: OutputFormat) -> Result<String, Error> {
+        with_default_path(|path| self.run_raw_custom_path(path, format))
+    }
+
+    /// Invokes a vswhere instance at the specified path, returning its output in the specified
+    /// format without parsing it.
+    ///
+    /// This is primarily useful for logging or reproducing the output of vswhere exactly. Any
+    /// bytes that are not valid UTF-8 are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn run_raw_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: OutputFormat,
+    ) -> Result<String, Error> {
+        let mut cmd = self.command_with_format(path, format);
+        self.output(&mut cmd).and_then(|output| {
+            successful_stdout(&output)
+                .map(|stdout| decode_stdout(stdout, self.output_code_page()).into_owned())
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance installed in a default location, using the
+    /// current configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_default_path`, and is only available
+    /// if the `tokio` feature is enabled.
+    pub fn run_default_path_async(
+        &self,
+    ) -> impl Future<Output = Result<Vec<InstallInfo>, Error>> + Send + '_ {
+        with_default_path_async(move |path| self.run_custom_path_async(path))
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance at the specified path, using the current
+    /// configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_custom_path`, and is only available
+    /// if the `tokio` feature is enabled.
+    pub fn run_custom_path_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> impl Future<Output = Result<Vec<InstallInfo>, Error>> + Send {
+        let (prerelease, sort_locally) = (self.prerelease, self.sort_locally);
+        self.run_json_async(self.command(path))
+            .map_ok(move |instances| filter_instances(prerelease, sort_locally, instances))
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance installed in a default location, retrieving the
+    /// value of a single property for each Visual Studio installation that matches the current
+    /// configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_property_default_path`, and is only
+    /// available if the `tokio` feature is enabled.
+    pub fn run_property_default_path_async<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Future<Output = Result<Vec<String>, Error>> + Send + 'a {
+        with_default_path_async(move |path| self.run_property_custom_path_async(path, name))
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance at the specified path, retrieving the value of a
+    /// single property for each Visual Studio installation that matches the current
+    /// configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_property_custom_path`, and is only
+    /// available if the `tokio` feature is enabled.
+    pub fn run_property_custom_path_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+    ) -> impl Future<Output = Result<Vec<String>, Error>> + Send {
+        let mut cmd = self.command(path);
+        let _ = cmd.args(["-property", name]);
+        self.run_json_async(cmd).map_ok(property_values)
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance installed in a default location, searching for
+    /// files within each Visual Studio installation that matches the current configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_find_default_path`, and is only
+    /// available if the `tokio` feature is enabled.
+    pub fn run_find_default_path_async<'a>(
+        &'a self,
+        pattern: &'a str,
+    ) -> impl Future<Output = Result<Vec<PathBuf>, Error>> + Send + 'a {
+        with_default_path_async(move |path| self.run_find_custom_path_async(path, pattern))
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Asynchronously invokes a vswhere instance at the specified path, searching for files
+    /// within each Visual Studio installation that matches the current configuration.
+    ///
+    /// This is the asynchronous equivalent of `Config::run_find_custom_path`, and is only
+    /// available if the `tokio` feature is enabled.
+    pub fn run_find_custom_path_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pattern: &str,
+    ) -> impl Future<Output = Result<Vec<PathBuf>, Error>> + Send {
+        let mut cmd = self.command(path);
+        let _ = cmd.args(["-find", pattern]);
+        self.run_json_async(cmd)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn run_json_async<T: DeserializeOwned>(
+        &self,
+        cmd: Command,
+    ) -> impl Future<Output = Result<T, Error>> + Send {
+        let code_page = self.output_code_page();
+        self.output_async(cmd)
+            .map(move |output| output.and_then(|output| parse_output(&output, code_page)))
+    }
+
+    #[cfg(feature = "tokio")]
+    fn output_async(&self, cmd: Command) -> impl Future<Output = Result<Output, Error>> {
+        if let Err(e) = self.validate() {
+            return Either::Left(future::err(e.into()));
+        }
+        let (runner, timeout) = (self.runner.clone(), self.timeout);
+        // Defer spawning until the future is polled, since that requires a tokio runtime.
+        Either::Right(future::lazy(move |_| spawn_async(cmd, runner, timeout)).flatten())
+    }
+
+    fn run_json<T: DeserializeOwned>(&self, cmd: &mut Command) -> Result<T, Error> {
+        self.output(cmd)
+            .and_then(|output| parse_output(&output, self.output_code_page()))
+    }
+
+    /// Checks the current configuration for mistakes that would prevent it from ever matching a
+    /// Visual Studio installation, such as a version number range whose lower bound is greater
+    /// than its upper bound, or an empty range like `v..v`.
+    ///
+    /// This is checked automatically before vswhere is run, in which case any problem is
+    /// returned as `Error::Config` instead of vswhere silently returning no results.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some((start, end)) = self.version {
+            match (start, end) {
+                (
+                    Bound::Included(start) | Bound::Excluded(start),
+                    Bound::Included(end) | Bound::Excluded(end),
+                ) if start > end => return Err(ConfigError::InvertedVersionRange(start, end)),
+                (Bound::Excluded(start), Bound::Included(end) | Bound::Excluded(end))
+                | (Bound::Included(start), Bound::Excluded(end))
+                    if start == end =>
+                {
+                    return Err(ConfigError::EmptyVersionRange(start))
+                }
+                _ => {}
+            }
+        }
+        if self.latest && self.prerelease == PrereleaseFilter::Only {
+            return Err(ConfigError::LatestWithPrereleaseOnly);
+        }
+        Ok(())
+    }
+
+    /// Retrieves the version number of a vswhere instance installed in a default location.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `Config::vswhere_version_custom_path` for more information.
+    pub fn vswhere_version_default_path(&self) -> Result<Version, Error> {
+        with_default_path(|path| self.vswhere_version_custom_path(path))
+    }
+
+    /// Retrieves the version number of a vswhere instance at the specified path.
+    ///
+    /// This runs vswhere with the `-?` flag and parses the version number from the banner that it
+    /// prints. The environment, working directory, runner and timeout of the current
+    /// configuration are used, but its filters are not, and it is not validated.
+    /// `Error::UnknownVersion` is returned if no version number can be found in the banner.
+    pub fn vswhere_version_custom_path<P: AsRef<Path>>(&self, path: P) -> Result<Version, Error> {
+        let mut cmd = Command::new(path.as_ref());
+        self.prepare_environment(&mut cmd);
+        let _ = cmd.arg("-?");
+        let output = self.output_unchecked(&mut cmd)?;
+        let banner = String::from_utf8_lossy(successful_stdout(&output)?);
+        parse_vswhere_version(&banner).ok_or_else(|| Error::UnknownVersion(banner.into_owned()))
+    }
+
+    /// Checks the current configuration as per `Config::validate`, and also checks that every
+    /// argument returned by `Config::args` is supported by the given version of vswhere.
+    ///
+    /// The version would typically be retrieved via `VsWhere::version`. See
+    /// `min_vswhere_version` for the flags whose support is checked.
+    pub fn validate_for(&self, vswhere_version: &Version) -> Result<(), ConfigError> {
+        self.validate()?;
+        let version = Version::new(
+            vswhere_version.major,
+            vswhere_version.minor,
+            vswhere_version.patch,
+        );
+        for arg in self.args() {
+            if let Some((flag, min_version)) = arg.to_str().and_then(flag_min_version) {
+                if version < min_version {
+                    return Err(ConfigError::UnsupportedFlag(flag, min_version));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn output_code_page(&self) -> Option<u32> {
+        // `CP_ACP`, the system default (ANSI) code page.
+        const ANSI_CODE_PAGE: u32 = 0;
+
+        if self.utf8 {
+            None
+        } else {
+            Some(self.code_page.unwrap_or(ANSI_CODE_PAGE))
+        }
+    }
+
+    fn output(&self, cmd: &mut Command) -> Result<Output, Error> {
+        self.validate()?;
+        self.output_unchecked(cmd)
+    }
+
+    fn output_unchecked(&self, cmd: &mut Command) -> Result<Output, Error> {
+        debug!("running {cmd:?}");
+        match self.runner.as_ref() {
+            Some(runner) => runner.output(cmd, self.timeout),
+            None => ProcessRunner.output(cmd, self.timeout),
+        }
+    }
+
+    fn command<P: AsRef<Path>>(&self, path: P) -> Command {
+        self.command_with_format(path, OutputFormat::Json)
+    }
+
+    /// Configures an existing command to run vswhere using the current configuration.
+    ///
+    /// The arguments returned by `Config::args` are appended to the command, and any environment
+    /// variables and working directory set on this `Config` are applied to it. This allows the
+    /// caller to configure and spawn vswhere itself (e.g. to redirect standard input, or assign
+    /// the process to a job object), then parse its output using `parse_instances`. Note that
+    /// the timeout and runner set on this `Config` are not used, and that its output must be
+    /// decoded as the system default code page first if `Config::utf8` is disabled.
+    pub fn prepare(&self, cmd: &mut Command) {
+        self.prepare_with_format(cmd, OutputFormat::Json);
+    }
+
+    fn prepare_environment(&self, cmd: &mut Command) {
+        if self.env_clear {
+            let _ = cmd.env_clear();
+        }
+        for (key, value) in &self.envs {
+            let _ = match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+        if let Some(dir) = self.current_dir.as_ref() {
+            let _ = cmd.current_dir(dir);
+        }
+    }
+
+    fn command_with_format<P: AsRef<Path>>(&self, path: P, format: OutputFormat) -> Command {
+        let mut cmd = Command::new(path.as_ref());
+        self.prepare_with_format(&mut cmd, format);
+        cmd
+    }
+
+    fn prepare_with_format(&self, cmd: &mut Command, format: OutputFormat) {
+        self.prepare_environment(cmd);
+        if self.prerelease != PrereleaseFilter::Exclude {
+            let _ = cmd.arg("-prerelease");
+        }
+        let _ = cmd.arg("-products");
+        if self.products.is_empty() {
+            let _ = cmd.arg("*");
+        } else {
+            let _ = cmd.args(&self.products);
+        }
+        if !self.requires.is_empty() {
+            let _ = cmd.arg("-requires").args(&self.requires);
+        }
+        if self.requires_any {
+            let _ = cmd.arg("-requiresAny");
+        }
+        if let Some((start, end)) = self.version {
+            let _ = cmd.args(["-version", &version_range_arg(start, end)]);
+        }
+        if self.all {
+            let _ = cmd.arg("-all");
+        }
+        if self.latest {
+            let _ = cmd.arg("-latest");
+        }
+        if self.sort {
+            let _ = cmd.arg("-sort");
+        }
+        let format_arg = match format {
+            OutputFormat::Json => "json",
+            OutputFormat::Text => "text",
+            OutputFormat::Value => "value",
+            OutputFormat::Xml => "xml",
+        };
+        let _ = cmd.args(["-nologo", "-format", format_arg]);
+        if !self.color && matches!(format, OutputFormat::Text | OutputFormat::Value) {
+            let _ = cmd.arg("-nocolor");
+        }
+        if self.utf8 {
+            let _ = cmd.arg("-utf8");
+        }
+        let _ = cmd.args(&self.raw_args);
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner for ProcessRunner {
+    fn output(&self, cmd: &mut Command, timeout: Option<Duration>) -> Result<Output, Error> {
+        match timeout {
+            Some(timeout) => output_with_timeout(cmd, timeout),
+            None => cmd.output().map_err(spawn_error),
+        }
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl MockRunner {
+    /// Creates a `MockRunner` that reports a successful exit and the given standard output.
+    pub fn new<T: Into<Vec<u8>>>(stdout: T) -> Self {
+        Self {
+            code: 0,
+            stdout: stdout.into(),
+            stderr: Vec::new(),
+        }
+    }
+
+    /// Specifies the exit code to report.
+    ///
+    /// By default this is `0`, indicating success.
+    pub fn exit_code(&mut self, code: i32) -> &mut Self {
+        self.code = code;
+        self
+    }
+
+    /// Specifies the standard error output to report.
+    ///
+    /// By default this is empty.
+    pub fn stderr<T: Into<Vec<u8>>>(&mut self, stderr: T) -> &mut Self {
+        self.stderr = stderr.into();
+        self
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl Runner for MockRunner {
+    fn output(&self, _: &mut Command, _: Option<Duration>) -> Result<Output, Error> {
+        #[cfg(unix)]
+        let status = {
+            use std::os::unix::process::ExitStatusExt;
+            ExitStatus::from_raw((self.code & 0xff) << 8)
+        };
+        #[cfg(windows)]
+        let status = {
+            use std::os::windows::process::ExitStatusExt;
+            ExitStatus::from_raw(u32::from_ne_bytes(self.code.to_ne_bytes()))
+        };
+        Ok(Output {
+            status,
+            stdout: self.stdout.clone(),
+            stderr: self.stderr.clone(),
+        })
+    }
+}
+
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+impl VsWhere {
+    /// Searches for a vswhere instance installed in a default location.
+    ///
+    /// The same locations are searched in the same order as `Config::run_default_path`, except
+    /// that the path given by `VSWHERE_PATH` is used without checking that it exists.
+    /// `Error::NotFound` is returned if no vswhere instance could be found.
+    ///
+    /// Besides avoiding repeated searches, this is useful for diagnosing unexpected results,
+    /// since `VsWhere::path` reveals exactly which executable is being run. For instance, on
+    /// ARM64 systems an outdated vswhere bundled with Visual Studio Installer may run under
+    /// emulation and report no installations. `default_path_candidates` lists every location
+    /// that is searched.
+    pub fn locate() -> Result<Self, Error> {
+        locate_default_path().map(Self::from_path)
+    }
+
+    /// Finds every vswhere instance installed in a default location, without running any of
+    /// them.
+    ///
+    /// If `VSWHERE_PATH` is set, the path it gives is listed first, without checking that it
+    /// exists, as with `VsWhere::locate`. It is followed by every other location searched by
+    /// `VsWhere::locate` where vswhere exists, in the same order, even though `VsWhere::locate`
+    /// ignores them while `VSWHERE_PATH` is set. The first element, if any, is therefore the
+    /// instance that `VsWhere::locate` would return. This is intended for diagnostics, e.g.
+    /// detecting an outdated copy of vswhere that shadows a newer one.
+    pub fn locate_all() -> Result<Vec<Self>, Error> {
+        let mut paths: Vec<PathBuf> = override_path().into_iter().collect();
+        match known_folder_paths() {
+            Ok(candidates) => {
+                for path in candidates {
+                    if path.is_file() && !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+            Err(e) if paths.is_empty() => return Err(e),
+            Err(_) => (),
+        }
+        Ok(paths.into_iter().map(Self::from_path).collect())
+    }
+
+    /// Searches for a vswhere instance in each of the specified directories, in order.
+    ///
+    /// The first directory containing a file named `vswhere.exe` is used. `Error::NotFound` is
+    /// returned if none of the directories contain vswhere.
+    pub fn locate_in<I>(dirs: I) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        dirs.into_iter()
+            .map(|dir| dir.as_ref().join("vswhere.exe"))
+            .find(|path| path.is_file())
+            .map(Self::from_path)
+            .ok_or(Error::NotFound)
+    }
+
+    /// Writes an embedded copy of vswhere to a temporary location, and creates a `VsWhere` that
+    /// uses it.
+    ///
+    /// This allows self-contained tools to bundle vswhere (e.g. via `include_bytes!`) rather
+    /// than relying on a copy being installed; vswhere is licensed under the MIT license, which
+    /// permits redistribution. The executable is written to a subdirectory of
+    /// `std::env::temp_dir` named after a hash of its contents, and is reused by later calls if
+    /// it is already present with the same contents. `Error::Extract` is returned if it cannot
+    /// be written.
+    ///
+    /// This may safely be called concurrently from several threads or processes: each writes to
+    /// its own temporary file before moving it into place, and failing to replace a file that
+    /// another caller has already extracted (e.g. because it is running) is not an error.
+    pub fn extract<B: AsRef<[u8]>>(exe: B) -> Result<Self, Error> {
+        static EXTRACT_COUNT: AtomicUsize = AtomicUsize::new(0);
+        let exe = exe.as_ref();
+        let mut hasher = DefaultHasher::new();
+        exe.hash(&mut hasher);
+        let dir = env::temp_dir().join("vswhere-rs");
+        let path = dir.join(format!("vswhere-{:016x}.exe", hasher.finish()));
+        let is_extracted = |path: &Path| fs::read(path).ok().as_deref() == Some(exe);
+        if !is_extracted(&path) {
+            debug!("extracting vswhere to {}", path.display());
+            let temp_path = dir.join(format!(
+                "vswhere-{}-{}.tmp",
+                process::id(),
+                EXTRACT_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&dir)
+                .and_then(|()| fs::write(&temp_path, exe))
+                .and_then(|()| fs::rename(&temp_path, &path))
+                .or_else(|e| {
+                    let _ = fs::remove_file(&temp_path);
+                    if is_extracted(&path) {
+                        Ok(())
+                    } else {
+                        Err(e)
+                    }
+                })
+                .map_err(Error::Extract)?;
+        }
+        Ok(Self::from_path(path))
+    }
+
+    /// Creates a `VsWhere` that uses the vswhere instance at the specified path.
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path to the vswhere executable.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Invokes vswhere using the specified configuration.
+    ///
+    /// This is equivalent to `Config::run_custom_path`.
+    pub fn run(&self, config: &Config) -> Result<Vec<InstallInfo>, Error> {
+        config.run_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, deserializing its output into a
+    /// caller-supplied type.
+    ///
+    /// This is equivalent to `Config::run_custom_path_as`.
+    pub fn run_as<T: DeserializeOwned>(&self, config: &Config) -> Result<T, Error> {
+        config.run_custom_path_as(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, returning the number of matching
+    /// Visual Studio installations.
+    ///
+    /// This is equivalent to `Config::count_custom_path`.
+    pub fn count(&self, config: &Config) -> Result<usize, Error> {
+        config.count_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, returning whether any Visual Studio
+    /// installation matches.
+    ///
+    /// This is equivalent to `Config::any_custom_path`.
+    pub fn any(&self, config: &Config) -> Result<bool, Error> {
+        config.any_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, returning whether any matching Visual
+    /// Studio installation provides a given component.
+    ///
+    /// This is equivalent to `Config::has_component_custom_path`.
+    pub fn has_component(&self, config: &Config, component_id: &str) -> Result<bool, Error> {
+        config.has_component_custom_path(&self.path, component_id)
+    }
+
+    /// Invokes vswhere using the specified configuration, retrieving the value of a single
+    /// property for each matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::run_property_custom_path`.
+    pub fn run_property(&self, config: &Config, name: &str) -> Result<Vec<String>, Error> {
+        config.run_property_custom_path(&self.path, name)
+    }
+
+    /// Invokes vswhere using the specified configuration, searching for files within each
+    /// matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::run_find_custom_path`.
+    pub fn run_find(&self, config: &Config, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+        config.run_find_custom_path(&self.path, pattern)
+    }
+
+    /// Invokes vswhere using the specified configuration, searching for the MSBuild executable
+    /// within the latest matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::find_msbuild_custom_path`.
+    pub fn find_msbuild(&self, config: &Config) -> Result<Option<PathBuf>, Error> {
+        config.find_msbuild_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, searching for `vcvarsall.bat` within
+    /// the latest matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::find_vcvarsall_custom_path`.
+    pub fn find_vcvarsall(&self, config: &Config) -> Result<Option<PathBuf>, Error> {
+        config.find_vcvarsall_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, searching for `VsDevCmd.bat` within
+    /// the latest matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::find_vsdevcmd_custom_path`.
+    pub fn find_vsdevcmd(&self, config: &Config) -> Result<Option<PathBuf>, Error> {
+        config.find_vsdevcmd_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, resolving the tools needed to build
+    /// native code from the newest usable matching Visual Studio installation.
+    ///
+    /// This is equivalent to `Config::find_vs_tools_custom_path`.
+    pub fn find_vs_tools(&self, config: &Config) -> Result<Option<VsTools>, Error> {
+        config.find_vs_tools_custom_path(&self.path)
+    }
+
+    /// Invokes vswhere using the specified configuration, searching for the `cl.exe` that runs
+    /// on and targets the given architectures within the latest matching Visual Studio
+    /// installation.
+    ///
+    /// This is equivalent to `Config::find_cl_custom_path`.
+    pub fn find_cl(
+        &self,
+        config: &Config,
+        host: Arch,
+        target: Arch,
+    ) -> Result<Option<PathBuf>, Error> {
+        config.find_cl_custom_path(&self.path, host, target)
+    }
+
+    /// Retrieves the version number of vswhere.
+    ///
+    /// This is equivalent to `Config::vswhere_version_custom_path`.
+    pub fn version(&self, config: &Config) -> Result<Version, Error> {
+        config.vswhere_version_custom_path(&self.path)
+    }
+}
+
+impl PartialEq for InstallInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.instance_id == other.instance_id
+    }
+}
+
+impl Eq for InstallInfo {}
+
+impl Hash for InstallInfo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.instance_id.hash(state);
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for InstallInfo {
+    type Error = Error;
+
+    /// Converts a JSON value describing a single installation, as retrieved via
+    /// `Config::run_custom_path_as`, into an `InstallInfo` without running vswhere again.
+    fn try_from(value: &'a Value) -> Result<Self, Error> {
+        InstallInfo::deserialize(value).map_err(Error::Parse)
+    }
+}
+
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+impl InstallInfo {
+    /// Returns the string that uniquely identifies a Visual Studio instance.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Returns the date and time when a Visual Studio instance was installed.
+    pub fn install_date(&self) -> &DateTime<Utc> {
+        &self.install_date
+    }
+
+    /// Returns the internal name of a Visual Studio instance.
+    ///
+    /// This identifies the exact release that is installed (e.g.
+    /// `VisualStudio/15.7.2+27703.2018`), and is intended for machines rather than people; see
+    /// `InstallInfo::display_name` for a human-readable name.
+    pub fn installation_name(&self) -> &str {
+        &self.installation_name
+    }
+
+    /// Returns the build metadata from the internal name of a Visual Studio instance, if any.
+    ///
+    /// This is the part of `InstallInfo::installation_name` after the `+` sign (e.g.
+    /// `27703.2018` for `VisualStudio/15.7.2+27703.2018`).
+    pub fn build_metadata(&self) -> Option<&str> {
+        self.installation_name
+            .split_once('+')
+            .map(|(_, metadata)| metadata)
+    }
+
+    /// Returns the filesystem path to a Visual Studio instance.
+    ///
+    /// Paths are returned exactly as reported by vswhere, and so use backslashes as separators.
+    /// `to_forward_slashes` can be used to convert them for tools that do not accept
+    /// backslashes.
+    pub fn installation_path(&self) -> &Path {
+        &self.installation_path
+    }
+
+    /// Returns the product version number for a Visual Studio instance.
+    pub fn installation_version(&self) -> &FourPointVersion {
+        &self.installation_version
+    }
+
+    /// Returns the product ID for a Visual Studio instance.
+    pub fn product_id(&self) -> &str {
+        &self.product_id
+    }
+
+    /// Returns the edition of a Visual Studio instance, if its product ID is recognised.
+    pub fn edition(&self) -> Option<Edition> {
+        Edition::from_product_id(&self.product_id)
+    }
+
+    /// Returns the filesystem path to the main executable for a Visual Studio instance.
+    ///
+    /// Some versions of vswhere report this path relative to the installation path; see
+    /// `InstallInfo::full_product_path` for a path that is always absolute.
+    pub fn product_path(&self) -> &Path {
+        &self.product_path
+    }
+
+    /// Returns the absolute filesystem path to the main executable for a Visual Studio
+    /// instance.
+    ///
+    /// If vswhere reported a relative product path, it is joined onto the installation path;
+    /// otherwise the product path is returned unchanged.
+    pub fn full_product_path(&self) -> PathBuf {
+        self.installation_path.join(&self.product_path)
+    }
+
+    /// Returns the version numbers of the MSVC toolsets installed side by side within a Visual
+    /// Studio instance, sorted from newest to oldest.
+    ///
+    /// This reads the names of the folders beneath `VC\Tools\MSVC` in the installation path
+    /// (e.g. `14.29.30133`), ignoring any that are not version numbers. An empty list is
+    /// returned if the C++ build tools are not installed.
+    pub fn msvc_toolset_versions(&self) -> io::Result<Vec<FourPointVersion>> {
+        let dir = self.installation_path.join("VC").join("Tools").join("MSVC");
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut versions = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Some(version) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|n| n.parse::<FourPointVersion>().ok())
+                {
+                    versions.push(version);
+                }
+            }
+        }
+        versions.sort_by_key(|&version| cmp::Reverse(version));
+        Ok(versions)
+    }
+
+    /// Returns `true` if a Visual Studio instance is a prerelease version, or `false` otherwise.
+    pub fn is_prerelease(&self) -> bool {
+        self.is_prerelease
+    }
+
+    /// Returns `true` if a Visual Studio instance is completely installed, or `false` if it is
+    /// partially installed (e.g. because installation was interrupted or failed).
+    ///
+    /// If vswhere does not report this information, the instance is assumed to be complete.
+    pub fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    /// Returns `true` if a Visual Studio instance can be launched, or `false` otherwise.
+    ///
+    /// If vswhere does not report this information, the instance is assumed to be launchable.
+    pub fn is_launchable(&self) -> bool {
+        self.is_launchable
+    }
+
+    /// Returns `true` if a Visual Studio instance requires a reboot before it can be used, or
+    /// `false` otherwise.
+    ///
+    /// If vswhere does not report this information, no reboot is assumed to be required.
+    pub fn is_reboot_required(&self) -> bool {
+        self.is_reboot_required
+    }
+
+    /// Returns `true` if a Visual Studio instance is complete, launchable and does not require a
+    /// reboot, or `false` otherwise.
+    ///
+    /// Installations that are incomplete or cannot be launched are only reported when
+    /// `Config::include_incomplete` is enabled, but those that require a reboot may be reported
+    /// regardless.
+    pub fn is_usable(&self) -> bool {
+        self.is_complete && self.is_launchable && !self.is_reboot_required
+    }
+
+    /// Invokes a vswhere instance installed in a default location, retrieving up-to-date
+    /// information about this Visual Studio installation.
+    ///
+    /// See `Config::run_default_path` for the list of locations that are searched, and
+    /// `InstallInfo::refresh_custom_path` for more information.
+    pub fn refresh_default_path(&self) -> Result<Option<InstallInfo>, Error> {
+        with_default_path(|path| self.refresh_custom_path(path))
+    }
+
+    /// Invokes a vswhere instance at the specified path, retrieving up-to-date information about
+    /// this Visual Studio installation.
+    ///
+    /// This allows "pinning" a previously-found installation and re-resolving it later, e.g. after
+    /// it has been updated. The installation is found by its instance ID or, failing that (e.g.
+    /// if it has been reinstalled), by an installation path that is exactly the same, compared
+    /// without regard to ASCII case. The search uses `Config::everything`, so `None` is only
+    /// returned if no such installation exists any more. Note that
+    /// `Config::run_custom_path_by_location` finds the installation *containing* a path, which
+    /// may be an enclosing one if installations are nested, so tools that only store the
+    /// installation path (such as a user's chosen toolchain) should compare it against
+    /// `InstallInfo::installation_path` of each installation instead.
+    pub fn refresh_custom_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<InstallInfo>, Error> {
+        Config::everything()
+            .run_custom_path(path)
+            .map(|instances| self.find_in(instances))
+    }
+
+    /// Finds this installation among the given ones, as per `InstallInfo::refresh_custom_path`.
+    fn find_in(&self, instances: Vec<InstallInfo>) -> Option<InstallInfo> {
+        let mut same_path = None;
+        for instance in instances {
+            if instance.instance_id == self.instance_id {
+                return Some(instance);
+            }
+            if same_path.is_none() && path_eq(&instance.installation_path, &self.installation_path)
+            {
+                same_path = Some(instance);
+            }
+        }
+        same_path
+    }
+
+    /// Returns the human-readable name of a Visual Studio instance (e.g.
+    /// `Visual Studio Community 2017`).
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns the human-readable description for a Visual Studio instance.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the ID of the release channel that a Visual Studio instance is associated with
+    /// (e.g. `VisualStudio.16.Release`).
+    pub fn channel_id(&self) -> &str {
+        &self.channel_id
+    }
+
+    /// Returns the release channel that a Visual Studio instance is associated with, if its
+    /// channel ID is recognised.
+    ///
+    /// This is independent of `InstallInfo::is_prerelease`: an installation from the preview
+    /// channel is not necessarily reported as a pre-release version, so this is the more
+    /// reliable way to exclude previews.
+    pub fn channel(&self) -> Option<Channel> {
+        Channel::from_channel_id(&self.channel_id)
+    }
+
+    /// Returns the filesystem path to the catalog file for the release channel that a Visual
+    /// Studio instance is associated with.
+    pub fn channel_path(&self) -> &Path {
+        &self.channel_path
+    }
+
+    /// Returns the URL from where release channel updates are fetched.
+    pub fn channel_url(&self) -> &Url {
+        &self.channel_uri
+    }
+
+    /// Returns the filesystem path to the Visual Studio Installer engine that manages a Visual
+    /// Studio instance.
+    ///
+    /// Unlike the product path, this is not located within the installation path.
+    pub fn engine_path(&self) -> &Path {
+        &self.engine_path
+    }
+
+    /// Returns the URL to the release notes for a Visual Studio instance.
+    pub fn release_notes(&self) -> &Url {
+        &self.release_notes
+    }
+
+    /// Returns the URL to the third-party notices for a Visual Studio instance.
+    pub fn third_party_notices(&self) -> &Url {
+        &self.third_party_notices
+    }
+
+    /// Returns the date and time when a Visual Studio instance was last updated.
+    pub fn update_date(&self) -> &DateTime<Utc> {
+        &self.update_date
+    }
+
+    /// Returns the catalog information for a Visual Studio instance.
+    pub fn catalog(&self) -> &InstallCatalog {
+        &self.catalog
+    }
+
+    /// Returns the property information for a Visual Studio instance.
+    pub fn properties(&self) -> &InstallProperties {
+        &self.properties
+    }
+
+    /// Returns any fields reported by vswhere that are not otherwise exposed by `InstallInfo`.
+    ///
+    /// Newer versions of vswhere may report additional information about a Visual Studio
+    /// instance; rather than being discarded, such fields are collected here as raw JSON values,
+    /// keyed by their original (camel-cased) names.
+    pub fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl InstallCatalog {
+    /// Returns the name of the source branch that a Visual Studio instance was built from (e.g.
+    /// `d15.7`).
+    pub fn build_branch(&self) -> &str {
+        &self.build_branch
+    }
+
+    /// Returns the build version number for a Visual Studio instance.
+    pub fn build_version(&self) -> &FourPointVersion {
+        &self.build_version
+    }
+
+    /// Returns the ID of the catalog that a Visual Studio instance was installed from.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the local build identifier for a Visual Studio instance.
+    pub fn local_build(&self) -> &str {
+        &self.local_build
+    }
+
+    /// Returns the name of the manifest that a Visual Studio instance was installed from.
+    pub fn manifest_name(&self) -> &str {
+        &self.manifest_name
+    }
+
+    /// Returns the type of the manifest that a Visual Studio instance was installed from.
+    pub fn manifest_type(&self) -> &str {
+        &self.manifest_type
+    }
+
+    /// Returns the human-readable version number for a Visual Studio instance.
+    pub fn product_display_version(&self) -> &str {
+        &self.product_display_version
+    }
+
+    /// Returns the internal name of the product line that a Visual Studio instance belongs to (e.g.
+    /// `Dev15`).
+    pub fn product_line(&self) -> &str {
+        &self.product_line
+    }
+
+    /// Returns the year-based version of the product line that a Visual Studio instance belongs
+    /// to (e.g. `2017`).
+    pub fn product_line_version(&self) -> &str {
+        &self.product_line_version
+    }
+
+    /// Returns the release milestone of a Visual Studio instance (e.g. `RTW` or `Preview`).
+    pub fn product_milestone(&self) -> &str {
+        &self.product_milestone
+    }
+
+    /// Returns `true` if the release milestone of a Visual Studio instance is a pre-release
+    /// milestone, or `false` otherwise.
+    pub fn product_milestone_is_pre_release(&self) -> bool {
+        self.product_milestone_is_pre_release
+    }
+
+    /// Returns the name of the product that a Visual Studio instance is an edition of (e.g.
+    /// `Visual Studio`).
+    pub fn product_name(&self) -> &str {
+        &self.product_name
+    }
+
+    /// Returns the patch version number for a Visual Studio instance.
+    pub fn product_patch_version(&self) -> &str {
+        &self.product_patch_version
+    }
+
+    /// Returns the pre-release milestone suffix for a Visual Studio instance.
+    pub fn product_pre_release_milestone_suffix(&self) -> &str {
+        &self.product_pre_release_milestone_suffix
+    }
+
+    /// Returns the release name for a Visual Studio instance (e.g. `RTW`).
+    pub fn product_release(&self) -> &str {
+        &self.product_release
+    }
+
+    /// Returns the semver-compliant version number for a Visual Studio instance.
+    pub fn product_semantic_version(&self) -> &Version {
+        &self.product_semantic_version
+    }
+
+    /// Returns the minimum version of the Visual Studio Installer engine required to install a
+    /// Visual Studio instance.
+    pub fn required_engine_version(&self) -> &FourPointVersion {
+        &self.required_engine_version
+    }
+
+    /// Returns any catalog fields reported by vswhere that are not otherwise exposed by
+    /// `InstallCatalog`, keyed by their original (camel-cased) names.
+    pub fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl InstallProperties {
+    /// Returns the ID of the campaign through which a Visual Studio instance was installed, or
+    /// an empty string if it was not installed as part of a campaign.
+    pub fn campaign_id(&self) -> &str {
+        &self.campaign_id
+    }
+
+    /// Returns the ID of the channel manifest that a Visual Studio instance was installed from
+    /// (e.g. `VisualStudio.15.Release/15.7.2+27703.2018`).
+    ///
+    /// Unlike `InstallInfo::channel_id`, this includes the exact release that was installed.
+    pub fn channel_manifest_id(&self) -> &str {
+        &self.channel_manifest_id
+    }
+
+    /// Returns the user-assigned nickname for a Visual Studio instance.
+    pub fn nickname(&self) -> &str {
+        &self.nickname
+    }
+
+    /// Returns the filesystem path to the Visual Studio Installer executable that manages a
+    /// Visual Studio instance.
+    pub fn setup_engine_file_path(&self) -> &Path {
+        &self.setup_engine_file_path
+    }
+
+    /// Returns any properties reported by vswhere that are not otherwise exposed by
+    /// `InstallProperties`, keyed by their original (camel-cased) names.
+    pub fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+}
+
+#[allow(clippy::must_use_candidate)]
+impl VsTools {
+    fn resolve(instance: InstallInfo, prefer_64bit: bool) -> Self {
+        let root = instance.installation_path.clone();
+        let msbuild = ["Current", "15.0"]
+            .iter()
+            .flat_map(|version| {
+                let bin = root.join("MSBuild").join(version).join("Bin");
+                let amd64 = Some(bin.join("amd64")).filter(|_| prefer_64bit);
+                amd64.into_iter().chain(iter::once(bin))
+            })
+            .map(|bin| bin.join("MSBuild.exe"))
+            .find(|path| path.is_file());
+        let vc = root.join("VC");
+        let build = vc.join("Auxiliary").join("Build");
+        let vcvarsall = Some(build.join("vcvarsall.bat")).filter(|path| path.is_file());
+        let vc_tools_version =
+            fs::read_to_string(build.join("Microsoft.VCToolsVersion.default.txt"))
+                .ok()
+                .map(|version| version.trim().to_owned())
+                .filter(|version| !version.is_empty());
+        let host_compiler = vc_tools_version.as_ref().and_then(|version| {
+            let arch = Arch::current().name();
+            let path = vc
+                .join("Tools")
+                .join("MSVC")
+                .join(version)
+                .join("bin")
+                .join(format!("Host{arch}"))
+                .join(arch)
+                .join("cl.exe");
+            Some(path).filter(|path| path.is_file())
+        });
+        Self {
+            instance,
+            msbuild,
+            vcvarsall,
+            vc_tools_version,
+            host_compiler,
+        }
+    }
+
+    /// Returns information about the Visual Studio installation that the tools belong to.
+    pub fn instance(&self) -> &InstallInfo {
+        &self.instance
+    }
+
+    /// Returns the installation path of the Visual Studio installation that the tools belong to.
+    pub fn installation_path(&self) -> &Path {
+        &self.instance.installation_path
+    }
+
+    /// Returns the path to the MSBuild executable, if installed.
+    ///
+    /// As with `Config::find_msbuild_custom_path`, the MSBuild in the `Current` folder is
+    /// preferred over the one in the `15.0` folder, and the 64-bit MSBuild is only returned if
+    /// `Config::prefer_64bit_tools` was enabled.
+    pub fn msbuild(&self) -> Option<&Path> {
+        self.msbuild.as_deref()
+    }
+
+    /// Returns the path to `vcvarsall.bat`, if the C++ build tools are installed.
+    pub fn vcvarsall(&self) -> Option<&Path> {
+        self.vcvarsall.as_deref()
+    }
+
+    /// Returns the version of the default MSVC toolset (e.g. `14.29.30133`), if the C++ build
+    /// tools are installed.
+    ///
+    /// This is read from `VC\Auxiliary\Build\Microsoft.VCToolsVersion.default.txt`, and names
+    /// the folder beneath `VC\Tools\MSVC` that contains the toolset.
+    pub fn vc_tools_version(&self) -> Option<&str> {
+        self.vc_tools_version.as_deref()
+    }
+
+    /// Returns the path to the `cl.exe` from the default MSVC toolset that both runs on and
+    /// targets the current architecture (e.g. `bin\Hostx64\x64\cl.exe` on x86-64), if
+    /// installed.
+    pub fn host_compiler(&self) -> Option<&Path> {
+        self.host_compiler.as_deref()
+    }
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Parses previously captured JSON output from vswhere into information about Visual Studio
+/// installations.
+///
+/// This performs the same parsing as `Config::run_custom_path` without running vswhere, e.g. to
+/// interpret output captured by another process or cached on disk. The output must have been
+/// generated using `-format json`.
+pub fn parse_instances(json: &[u8]) -> Result<Vec<InstallInfo>, Error> {
+    parse_instances_as(json)
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Converts JSON values describing installations, as retrieved via `Config::run_custom_path_as`,
+/// into information about Visual Studio installations.
+///
+/// Each value is converted as per `InstallInfo::try_from`; the first value that cannot be
+/// converted causes `Error::Parse` to be returned.
+pub fn parse_instance_values(values: &[Value]) -> Result<Vec<InstallInfo>, Error> {
+    values.iter().map(InstallInfo::try_from).collect()
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Parses previously captured JSON output from vswhere into a caller-provided type.
+///
+/// This is the equivalent of `Config::run_custom_path_as` for output that has already been
+/// captured; see `parse_instances` for more information.
+pub fn parse_instances_as<T: DeserializeOwned>(json: &[u8]) -> Result<T, Error> {
+    serde_json::from_slice(json).map_err(Error::Parse)
+}
+
+/// Converts a path to a string that uses forward slashes as separators.
+///
+/// Paths reported by vswhere use backslashes, which some cross-platform tools (e.g. those
+/// running under WSL) do not accept. Windows itself accepts either separator, except in paths
+/// beginning with `\\?\`. Any parts of the path that are not valid Unicode are converted lossily.
+pub fn to_forward_slashes<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref().to_string_lossy().replace('\\', "/")
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Returns every location that is searched for vswhere by `Config::run_default_path`, in the
+/// order in which they are tried.
+///
+/// Locations are returned regardless of whether vswhere exists there. This is intended for
+/// diagnostics, e.g. reporting where vswhere was looked for when it could not be found.
+pub fn default_path_candidates() -> Result<Vec<PathBuf>, Error> {
+    default_paths()
+}
+
+#[allow(clippy::must_use_candidate)]
+/// Returns the oldest version of vswhere that supports a given flag, if known.
+///
+/// The flag may be given with a leading `-` or `/`, and is matched without regard to ASCII case
+/// as vswhere does. Only flags that were added after vswhere's first release are known, such as
+/// `-prerelease` (2.0.2) or `-utf8` (2.5.2); for flags that every release accepts (e.g. `-latest`
+/// or `-format`) and unknown flags, `None` is returned. Combined with `Config::utf8`, this allows
+/// a configuration to be checked against vswhere versions older than 2.5.2 via
+/// `Config::validate_for`.
+pub fn min_vswhere_version(flag: &str) -> Option<Version> {
+    flag_min_version(flag).map(|(_, version)| version)
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Retrieves the version number of a vswhere instance installed in a default location.
+///
+/// See `Config::run_default_path` for the list of locations that are searched.
+pub fn vswhere_version_default_path() -> Result<Version, Error> {
+    with_default_path(vswhere_version_custom_path)
+}
+
+#[allow(clippy::missing_errors_doc)]
+/// Retrieves the version number of a vswhere instance at the specified path.
+///
+/// This is equivalent to `Config::new().vswhere_version_custom_path(path)`; see
+/// `Config::vswhere_version_custom_path` for more information.
+pub fn vswhere_version_custom_path<P: AsRef<Path>>(path: P) -> Result<Version, Error> {
+    Config::new().vswhere_version_custom_path(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+    use serde::Serialize;
+    use serde_json::{self, Value};
+    use std::collections::HashSet;
+    use std::convert::TryFrom;
+    use std::env;
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::io::{self, ErrorKind};
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Output};
+    use std::time::{Duration, Instant};
+    use {
+        default_path_candidates, filter_instances, ids, is_unusable_candidate, min_vswhere_version,
+        output_with_timeout, parse_instance_values, parse_instances, parse_instances_as,
+        parse_vswhere_version, path_contains, single_instance, thread, to_forward_slashes,
+        vswhere_version_default_path, Arch, Channel, Component, Config, ConfigError, Edition,
+        Error, FourPointVersion, InstallInfo, MockRunner, OutputFormat, PrereleaseFilter,
+        ProductLine, RequiresFilter, Runner, VsWhere,
+    };
+
+    const SAMPLE_JSON: &str = r#"[
+  {
+    "instanceId": "2a9d4c4e",
+    "installDate": "2018-05-21T10:12:44Z",
+    "installationName": "VisualStudio/15.7.2+27703.2018",
+    "installationPath": "C:\\Program Files (x86)\\Microsoft Visual Studio\\2017\\Community",
+    "installationVersion": "15.7.27703.2018",
+    "productId": "Microsoft.VisualStudio.Product.Community",
+    "productPath": "C:\\Program Files (x86)\\Microsoft Visual Studio\\2017\\Community\\Common7\\IDE\\devenv.exe",
+    "isPrerelease": false,
+    "displayName": "Visual Studio Community 2017",
+    "description": "Free, fully-featured IDE for students, open-source and individual developers",
+    "channelId": "VisualStudio.15.Release",
+    "channelPath": "C:\\Users\\user\\AppData\\Local\\Microsoft\\VisualStudio\\Packages\\_Channels\\4CB340F5\\catalog.json",
+    "channelUri": "https://aka.ms/vs/15/release/channel",
+    "enginePath": "C:\\Program Files (x86)\\Microsoft Visual Studio\\Installer\\resources\\app\\ServiceHub\\Services\\Microsoft.VisualStudio.Setup.Service",
+    "releaseNotes": "https://go.microsoft.com/fwlink/?LinkId=660692#15.7.2",
+    "thirdPartyNotices": "https://go.microsoft.com/fwlink/?LinkId=660708",
+    "updateDate": "2018-05-21T10:12:44.4570187Z",
+    "catalog": {
+      "buildBranch": "d15.7",
+      "buildVersion": "15.7.27703.2018",
+      "id": "VisualStudio/15.7.2+27703.2018",
+      "localBuild": "build-lab",
+      "manifestName": "VisualStudio",
+      "manifestType": "installer",
+      "productDisplayVersion": "15.7.2",
+      "productLine": "Dev15",
+      "productLineVersion": "2017",
+      "productMilestone": "RTW",
+      "productMilestoneIsPreRelease": "False",
+      "productName": "Visual Studio",
+      "productPatchVersion": "2",
+      "productPreReleaseMilestoneSuffix": "1.0",
+      "productRelease": "RTW",
+      "productSemanticVersion": "15.7.2+27703.2018",
+      "requiredEngineVersion": "1.16.1187.57215",
+      "productArch": "x64"
+    },
+    "properties": {
+      "campaignId": "",
+      "channelManifestId": "VisualStudio.15.Release/15.7.2+27703.2018",
+      "nickname": "",
+      "setupEngineFilePath": "C:\\Program Files (x86)\\Microsoft Visual Studio\\Installer\\vs_installershell.exe"
+    },
+    "isLaunchable": true,
+    "state": 4294967295
+  }
+]"#;
+
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_default() {
+        let _ = Config::default().run_default_path().expect("failed");
+    }
+
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_full_product_path() {
+        for instance in Config::new().run_default_path().expect("failed") {
+            let product_path = instance.full_product_path();
+            assert!(product_path.is_absolute());
+            assert!(product_path.starts_with(instance.installation_path()));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_args() {
+        let _ = Config::new()
+            .find_prerelease_versions(true)
+            .whitelist_product_id("*")
+            .whitelist_component_id(ids::COMPONENT_VC_TOOLS_X86_X64)
+            .require_any_component(true)
+            .version_number_range(
+                FourPointVersion::new(u16::MIN, u16::MIN, u16::MIN, u16::MIN)
+                    ..FourPointVersion::new(u16::MAX, u16::MAX, u16::MAX, u16::MAX),
+            )
+            .only_latest_versions(true)
+            .sort_newest_first(true)
+            .run_default_path()
+            .expect("failed");
+    }
+
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_fake_product() {
+        let _ = Config::new()
+            .whitelist_product_id("The quick brown fox jumps over the lazy dog.")
+            .run_default_path()
+            .expect("failed");
+    }
+
+    #[test]
+    fn test_empty_ids() {
+        let mut config = Config::new();
+        let _ = config.whitelist_product_id("").whitelist_component_id(" ");
+        let args = config.args();
+        assert_eq!(args[..2], ["-products", "*"]);
+        assert!(!args.contains(&"-requires".into()));
+        let products = vec![String::new(), ids::PRODUCT_COMMUNITY.to_owned()];
+        let _ = config.whitelist_product_ids(&products);
+        assert_eq!(config.args()[..2], ["-products", ids::PRODUCT_COMMUNITY]);
+    }
+
+    #[test]
+    fn test_version_from_value() {
+        let instances: Vec<Value> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        assert_eq!(
+            FourPointVersion::from_value(&instances[0]["installationVersion"]),
+            Some(FourPointVersion::new(15, 7, 27703, 2018))
+        );
+        assert_eq!(
+            FourPointVersion::from_value(&instances[0]["isLaunchable"]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_env() {
+        let mut config = Config::new();
+        let _ = config
+            .env_remove("VSINSTALLDIR")
+            .env("VSWHERE_TEST", "1")
+            .current_dir(env::temp_dir());
+        let cmd = config.command("vswhere.exe");
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert_eq!(
+            envs,
+            [
+                ("VSINSTALLDIR".as_ref(), None),
+                ("VSWHERE_TEST".as_ref(), Some("1".as_ref()))
+            ]
+        );
+        assert_eq!(cmd.get_current_dir(), Some(env::temp_dir().as_ref()));
+    }
+
+    #[test]
+    fn test_major_minor() {
+        const VS2019: FourPointVersion = FourPointVersion::major_minor(16, 0);
+        assert_eq!(VS2019, FourPointVersion::new(16, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_version_range() {
+        let version_arg = |config: &Config| {
+            let args = config.args();
+            args.iter()
+                .position(|arg| arg == "-version")
+                .map(|i| args[i + 1].clone())
+        };
+        let v16 = FourPointVersion::major_minor(16, 0);
+        let v17 = FourPointVersion::major_minor(17, 0);
+        let mut config = Config::new();
+        assert_eq!(version_arg(&config), None);
+        let _ = config.version_range(v16..v17);
+        assert_eq!(version_arg(&config), Some("[16.0.0.0,17.0.0.0)".into()));
+        let _ = config.version_range(v16..=v17);
+        assert_eq!(version_arg(&config), Some("[16.0.0.0,17.0.0.0]".into()));
+        let _ = config.version_range(..v17);
+        assert_eq!(version_arg(&config), Some("(,17.0.0.0)".into()));
+        let _ = config.version_range(..);
+        assert_eq!(version_arg(&config), None);
+        let max = FourPointVersion::new(u16::MAX, u16::MAX, u16::MAX, u16::MAX);
+        let _ = config.version_range(max..=max);
+        assert_eq!(
+            version_arg(&config),
+            Some("[65535.65535.65535.65535,65535.65535.65535.65535]".into())
+        );
+    }
+
+    #[test]
+    fn test_path_contains() {
+        let root = Path::new("/vs/2019/Community");
+        assert!(path_contains(root, root));
+        assert!(path_contains(root, Path::new("/VS/2019/community/MSBuild")));
+        assert!(!path_contains(root, Path::new("/vs/2019")));
+        assert!(!path_contains(
+            root,
+            Path::new("/vs/2019/Enterprise/MSBuild")
+        ));
+    }
+
+    #[test]
+    fn test_single_instance() {
+        let instances: Vec<InstallInfo> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        assert_eq!(single_instance(Vec::new()).expect("failed"), None);
+        assert_eq!(
+            single_instance(instances.clone()).expect("failed").as_ref(),
+            instances.first()
+        );
+        match single_instance(vec![instances[0].clone(), instances[0].clone()]) {
+            Err(Error::Ambiguous(2)) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_prerelease_only_latest() {
+        let mut json: Value = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        let mut preview = json[0].clone();
+        preview["instanceId"] = "b6b0d5c2".into();
+        preview["installationVersion"] = "15.7.27625.0".into();
+        preview["isPrerelease"] = true.into();
+        json.as_array_mut().expect("failed").push(preview);
+        let mut config = Config::new();
+        let _ = config
+            .prerelease_filter(PrereleaseFilter::Only)
+            .runner(MockRunner::new(json.to_string()));
+        let latest = config
+            .run_latest_custom_path("vswhere.exe")
+            .expect("failed");
+        assert_eq!(
+            latest.as_ref().map(InstallInfo::instance_id),
+            Some("b6b0d5c2")
+        );
+        match config.find_msbuild_custom_path("vswhere.exe") {
+            Err(Error::Config(ConfigError::LatestWithPrereleaseOnly)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let _ = config.only_latest_versions(true);
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::LatestWithPrereleaseOnly)
+        );
+    }
+
+    /// A `Runner` that returns different output depending on whether `-latest` is passed.
+    #[derive(Debug)]
+    struct LatestRunner {
+        latest: MockRunner,
+        all: MockRunner,
+    }
+
+    impl Runner for LatestRunner {
+        fn output(&self, cmd: &mut Command, timeout: Option<Duration>) -> Result<Output, Error> {
+            if cmd.get_args().any(|arg| arg == "-latest") {
+                self.latest.output(cmd, timeout)
+            } else {
+                self.all.output(cmd, timeout)
+            }
+        }
+    }
+
+    /// Returns two installations, the second of which is nested inside the first.
+    fn nested_instances() -> (Value, Value) {
+        let json: Value = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        let mut outer = json[0].clone();
+        outer["installationPath"] = "C:/VS".into();
+        let mut inner = outer.clone();
+        inner["instanceId"] = "b6b0d5c2".into();
+        inner["installationPath"] = "C:/VS/Preview".into();
+        inner["installationVersion"] = "15.8.27825.0".into();
+        (outer, inner)
+    }
+
+    #[test]
+    fn test_has_component_nested() {
+        let (outer, inner) = nested_instances();
+        let mut config = Config::new();
+        let _ = config.only_latest_versions(true).runner(LatestRunner {
+            latest: MockRunner::new(Value::from(vec![inner.clone()]).to_string()),
+            all: MockRunner::new(Value::from(vec![outer]).to_string()),
+        });
+        assert!(!config
+            .has_component_custom_path("vswhere.exe", ids::COMPONENT_VC_LLVM_CLANG)
+            .expect("failed"));
+        let _ = config.runner(LatestRunner {
+            latest: MockRunner::new(Value::from(vec![inner.clone()]).to_string()),
+            all: MockRunner::new(Value::from(vec![inner]).to_string()),
+        });
+        assert!(config
+            .has_component_custom_path("vswhere.exe", ids::COMPONENT_VC_LLVM_CLANG)
+            .expect("failed"));
+    }
+
+    #[test]
+    fn test_refresh_nested() {
+        let (outer, inner) = nested_instances();
+        let parse = |value: &Value| InstallInfo::try_from(value).expect("failed");
+        let pinned = parse(&inner);
+        assert_eq!(pinned.find_in(vec![parse(&outer)]), None);
+        let mut updated = inner.clone();
+        updated["installationVersion"] = "15.9.28307.0".into();
+        let found = pinned.find_in(vec![parse(&outer), parse(&updated)]);
+        assert_eq!(to_value(found), to_value(Some(parse(&updated))));
+        let mut reinstalled = inner;
+        reinstalled["instanceId"] = "c3d4e5f6".into();
+        reinstalled["installationPath"] = "c:/vs/preview".into();
+        let found = pinned.find_in(vec![parse(&outer), parse(&reinstalled)]);
+        assert_eq!(to_value(found), to_value(Some(parse(&reinstalled))));
+    }
+
+    #[test]
+    fn test_serialize_config() {
+        let mut config = Config::new();
+        let _ = config
+            .prerelease_filter(PrereleaseFilter::Only)
+            .whitelist_product_id(ids::PRODUCT_COMMUNITY)
+            .whitelist_component_id(ids::COMPONENT_VC_TOOLS_X86_X64)
+            .product_line(ProductLine::Vs2019)
+            .timeout(Some(Duration::from_secs(10)))
+            .raw_arg("-all")
+            .env_remove("VSINSTALLDIR");
+        let json = serde_json::to_string(&config).expect("failed");
+        let deserialized: Config = serde_json::from_str(&json).expect("failed");
+        assert_eq!(deserialized.args(), config.args());
+        let default: Config = serde_json::from_str("{}").expect("failed");
+        assert_eq!(default.args(), Config::new().args());
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut config = Config::new();
+        let _ = config
+            .whitelist_product_id("*")
+            .raw_arg(r#"say "hi""#)
+            .raw_arg(r"C:\dir with spaces\")
+            .raw_arg("");
+        assert_eq!(
+            config.command_line(r"C:\Program Files (x86)\vswhere.exe"),
+            concat!(
+                r#""C:\Program Files (x86)\vswhere.exe" -products * -nologo -format json -utf8 "#,
+                r#""say \"hi\"" "C:\dir with spaces\\" """#,
+            )
+        );
+        assert!(Config::new()
+            .command_line(r"C:\odd dir\")
+            .starts_with(r#""C:\odd dir\" -products"#));
+    }
+
+    #[test]
+    fn test_mock_runner() {
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(SAMPLE_JSON));
+        let instances = config.run_custom_path("vswhere.exe").expect("failed");
+        assert_eq!(instances[0].instance_id(), "2a9d4c4e");
+        assert_eq!(config.count_custom_path("vswhere.exe").expect("failed"), 1);
+        let latest = config
+            .run_latest_custom_path("vswhere.exe")
+            .expect("failed");
+        assert_eq!(to_value(latest), to_value(instances.first()));
+        let mut failing = MockRunner::new("");
+        let _ = failing.exit_code(87).stderr("Error 0x57: bad argument");
+        let _ = config.runner(failing);
+        match config.run_custom_path("vswhere.exe") {
+            Err(Error::Exit {
+                code: Some(87),
+                ref stderr,
+            }) if stderr.contains("bad argument") => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_parse_instances() {
+        let instances = parse_instances(SAMPLE_JSON.as_bytes()).expect("failed");
+        assert_eq!(instances[0].instance_id(), "2a9d4c4e");
+        let values: Vec<Value> = parse_instances_as(SAMPLE_JSON.as_bytes()).expect("failed");
+        assert_eq!(values[0]["instanceId"], "2a9d4c4e");
+        match parse_instances(b"[{") {
+            Err(Error::Parse(_)) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_to_forward_slashes() {
+        let instances = parse_instances(SAMPLE_JSON.as_bytes()).expect("failed");
+        assert_eq!(
+            to_forward_slashes(instances[0].installation_path()),
+            "C:/Program Files (x86)/Microsoft Visual Studio/2017/Community"
+        );
+    }
+
+    #[test]
+    fn test_instance_identity() {
+        let instances = parse_instances(SAMPLE_JSON.as_bytes()).expect("failed");
+        let mut updated: Value = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        updated[0]["installationVersion"] = "15.8.28010.2003".into();
+        let updated: Vec<InstallInfo> = serde_json::from_value(updated).expect("failed");
+        assert_eq!(instances, updated);
+        let merged: HashSet<_> = instances.into_iter().chain(updated).collect();
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn test_has_component() {
+        let mut config = Config::new();
+        let _ = config
+            .requires_filter(RequiresFilter::Any(&[ids::COMPONENT_VC_ATL]))
+            .runner(MockRunner::new(SAMPLE_JSON));
+        assert!(config
+            .has_component_custom_path("vswhere.exe", ids::COMPONENT_VC_LLVM_CLANG)
+            .expect("failed"));
+        let _ = config.runner(MockRunner::new("[]"));
+        assert!(!config
+            .has_component_custom_path("vswhere.exe", Component::VcLlvmClang.id())
+            .expect("failed"));
 
-            unsafe {
-                let mut path = KnownFolderPath(ptr::null_mut());
-                let hres = SHGetKnownFolderPath(id, 0, ptr::null_mut(), &mut path.0);
-                if hres == S_OK {
-                    let mut wide_string = path.0;
-                    let mut len = 0;
-                    while wide_string.read() != 0 {
-                        wide_string = wide_string.offset(1);
-                        len += 1;
-                    }
-                    let ws_slice = slice::from_raw_parts(path.0, len);
-                    let os_string = OsString::from_wide(ws_slice);
-                    Ok(Path::new(&os_string).to_owned())
-                } else {
-                    Err(io::Error::last_os_error())
-                }
-            }
-        }
+        let _ = config
+            .only_latest_versions(true)
+            .runner(MockRunner::new(SAMPLE_JSON));
+        assert!(config
+            .has_component_custom_path("vswhere.exe", ids::COMPONENT_VC_LLVM_CLANG)
+            .expect("failed"));
+        let args = config.component_query(ids::COMPONENT_VC_LLVM_CLANG).args();
+        let requires = args
+            .iter()
+            .position(|arg| arg == "-requires")
+            .expect("missing -requires");
+        assert_eq!(args[requires + 1], ids::COMPONENT_VC_LLVM_CLANG);
+        assert!(!args.contains(&ids::COMPONENT_VC_ATL.into()));
+        assert!(!args.contains(&"-requiresAny".into()));
+        assert!(!args.contains(&"-latest".into()));
+    }
 
-        let pd = get_known_folder_path(&FOLDERID_ProgramData)
-            .map(|p| p.join(r"chocolatey\bin\vswhere.exe"))?;
-        self.run_custom_path(pd).or_else(|e| {
-            if e.kind() == ErrorKind::NotFound {
-                get_known_folder_path(&FOLDERID_ProgramFilesX86)
-                    .map(|p| p.join(r"Microsoft Visual Studio\Installer\vswhere.exe"))
-                    .and_then(|p| self.run_custom_path(p))
-            } else {
-                Err(e)
-            }
-        })
+    #[test]
+    fn test_find_msbuild_64bit() {
+        let paths = [
+            "C:/VS/MSBuild/15.0/Bin/MSBuild.exe",
+            "C:/VS/MSBuild/Current/Bin/amd64/MSBuild.exe",
+            "C:/VS/MSBuild/Current/Bin/MSBuild.exe",
+        ];
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(
+            serde_json::to_string(&paths).expect("failed"),
+        ));
+        let found = config
+            .find_msbuild_custom_path("vswhere.exe")
+            .expect("failed");
+        assert_eq!(found.as_deref(), Some(Path::new(paths[2])));
+        let _ = config.prefer_64bit_tools(true);
+        let found = config
+            .find_msbuild_custom_path("vswhere.exe")
+            .expect("failed");
+        assert_eq!(found.as_deref(), Some(Path::new(paths[1])));
     }
 
-    /// Invokes a vswhere instance at the specified path, using the current configuration.
-    ///
-    /// The specified path must point to an executable, rather than a folder.
-    pub fn run_custom_path<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<InstallInfo>> {
-        let mut cmd = Command::new(path.as_ref());
-        if self.prerelease {
-            let _ = cmd.arg("-prerelease");
-        }
-        let _ = cmd.arg("-products");
-        if self.products.is_empty() {
-            let _ = cmd.arg("*");
-        } else {
-            let _ = cmd.args(&self.products);
-        }
-        if !self.requires.is_empty() {
-            let _ = cmd.arg("-requires").args(&self.requires);
-        }
-        if self.requires_any {
-            let _ = cmd.arg("-requiresAny");
-        }
-        if let Some(version_range) = self.version.as_ref() {
-            let _ = cmd.args(&[
-                "-version",
-                &format!("[{},{})", version_range.start, version_range.end),
-            ]);
-        }
-        if self.latest {
-            let _ = cmd.arg("-latest");
+    #[test]
+    fn test_try_from_value() {
+        let values: Vec<Value> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        let instance = InstallInfo::try_from(&values[0]).expect("failed");
+        assert_eq!(instance.instance_id(), "2a9d4c4e");
+        assert_eq!(
+            to_value(parse_instance_values(&values).expect("failed")),
+            to_value([instance])
+        );
+        match InstallInfo::try_from(&Value::Null) {
+            Err(Error::Parse(_)) => {}
+            result => panic!("unexpected result: {:?}", result),
         }
-        cmd.args(&["-format", "json", "-utf8"])
-            .output()
-            .map(|output| {
-                assert!(output.status.success());
-                let json = str::from_utf8(&output.stdout).expect("vswhere returned invalid UTF-8");
-                serde_json::from_str(json).expect("vswhere returned invalid JSON")
-            })
     }
-}
 
-impl Default for Config {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_prepare() {
+        let mut config = Config::new();
+        let _ = config
+            .whitelist_edition(Edition::BuildTools)
+            .current_dir("C:/");
+        let mut cmd = Command::new("vswhere.exe");
+        let _ = cmd.arg("-prerelease");
+        config.prepare(&mut cmd);
+        let args: Vec<_> = cmd.get_args().map(OsStr::to_owned).collect();
+        assert_eq!(args[0], "-prerelease");
+        assert_eq!(args[1..], config.args()[..]);
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("C:/")));
     }
-}
 
-impl InstallInfo {
-    /// Returns the string that uniquely identifies a Visual Studio instance.
-    pub fn instance_id(&self) -> &str {
-        &self.instance_id
+    #[test]
+    fn test_sort_locally() {
+        let mut json: Value = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        let mut newer = json[0].clone();
+        newer["instanceId"] = "b6b0d5c2".into();
+        newer["installationVersion"] = "15.8.28010.2003".into();
+        json.as_array_mut().expect("failed").push(newer);
+        let mut config = Config::new();
+        let _ = config
+            .sort_newest_first_locally(true)
+            .runner(MockRunner::new(json.to_string()));
+        assert!(!config.args().contains(&"-sort".into()));
+        let instances = config.run_custom_path("vswhere.exe").expect("failed");
+        let ids: Vec<_> = instances.iter().map(InstallInfo::instance_id).collect();
+        assert_eq!(ids, ["b6b0d5c2", "2a9d4c4e"]);
     }
 
-    /// Returns the date and time when a Visual Studio instance was installed.
-    pub fn install_date(&self) -> &DateTime<Utc> {
-        &self.install_date
+    #[test]
+    fn test_requires_filter() {
+        let mut config = Config::new();
+        let _ = config
+            .whitelist_component_id(ids::COMPONENT_VC_ATL)
+            .requires_filter(RequiresFilter::Any(&[
+                ids::COMPONENT_VC_TOOLS_X86_X64,
+                ids::COMPONENT_VC_TOOLS_ARM64,
+            ]));
+        assert_eq!(
+            config.args()[2..6],
+            [
+                "-requires",
+                ids::COMPONENT_VC_TOOLS_X86_X64,
+                ids::COMPONENT_VC_TOOLS_ARM64,
+                "-requiresAny"
+            ]
+        );
     }
 
-    /// Returns the internal name of a Visual Studio instance.
-    pub fn installation_name(&self) -> &str {
-        &self.installation_name
+    #[test]
+    fn test_locate_in() {
+        let dir = env::temp_dir().join("vswhere-rs-test-locate-in");
+        fs::create_dir_all(&dir).expect("failed");
+        let path = dir.join("vswhere.exe");
+        fs::write(&path, b"").expect("failed");
+        let missing = env::temp_dir().join("vswhere-rs-test-missing");
+        let vswhere = VsWhere::locate_in(&[&missing, &dir]).expect("failed");
+        assert_eq!(vswhere.path(), path);
+        match VsWhere::locate_in(&[&missing]) {
+            Err(Error::NotFound) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        fs::remove_dir_all(&dir).expect("failed");
     }
 
-    /// Returns the filesystem path to a Visual Studio instance.
-    pub fn installation_path(&self) -> &Path {
-        &self.installation_path
+    #[test]
+    fn test_unusable_candidate() {
+        let spawn = |kind| Error::Spawn(io::Error::from(kind));
+        assert!(is_unusable_candidate(&Error::NotFound));
+        assert!(is_unusable_candidate(&spawn(ErrorKind::PermissionDenied)));
+        assert!(!is_unusable_candidate(&spawn(ErrorKind::Other)));
+        assert!(!is_unusable_candidate(&Error::TimedOut));
+        assert!(!is_unusable_candidate(&Error::Exit {
+            code: Some(87),
+            stderr: String::new(),
+        }));
+        let path = env::temp_dir().join("vswhere-rs-unusable.exe");
+        fs::write(&path, b"not an executable").expect("failed");
+        let e = Config::new()
+            .run_custom_path(&path)
+            .expect_err("ran successfully");
+        assert!(is_unusable_candidate(&e), "unexpected error: {:?}", e);
+        fs::remove_file(&path).expect("failed");
     }
 
-    /// Returns the product version number for a Visual Studio instance.
-    pub fn installation_version(&self) -> &FourPointVersion {
-        &self.installation_version
+    #[test]
+    fn test_version_neighbours() {
+        let v17 = FourPointVersion::major_minor(17, 0);
+        let before = FourPointVersion::new(16, u16::MAX, u16::MAX, u16::MAX);
+        assert_eq!(v17.predecessor(), Some(before));
+        assert_eq!(before.successor(), Some(v17));
+        assert_eq!(
+            FourPointVersion::new(16, 11, 2, 0).successor(),
+            Some(FourPointVersion::new(16, 11, 2, 1))
+        );
+        assert_eq!(FourPointVersion::new(0, 0, 0, 0).predecessor(), None);
+        let max = FourPointVersion::new(u16::MAX, u16::MAX, u16::MAX, u16::MAX);
+        assert_eq!(max.successor(), None);
     }
 
-    /// Returns the product ID for a Visual Studio instance.
-    pub fn product_id(&self) -> &str {
-        &self.product_id
+    /// Serializes a value to JSON, so that `InstallInfo`s can be compared by content rather than
+    /// only by instance ID.
+    fn to_value<T: Serialize>(value: T) -> Value {
+        serde_json::to_value(value).expect("failed")
     }
 
-    /// Returns the filesystem path to the main executable for a Visual Studio instance.
-    pub fn product_path(&self) -> &Path {
-        &self.product_path
+    /// Creates a fake Visual Studio installation containing empty tool files, returning the
+    /// vswhere output that describes it.
+    fn fake_installation(name: &str, toolsets: &[&str]) -> (PathBuf, String) {
+        let root = env::temp_dir().join(name);
+        let arch = Arch::current().name();
+        let build = root.join("VC").join("Auxiliary").join("Build");
+        let mut files = vec![
+            root.join("MSBuild")
+                .join("Current")
+                .join("Bin")
+                .join("MSBuild.exe"),
+            build.join("vcvarsall.bat"),
+        ];
+        for toolset in toolsets {
+            let bin = root
+                .join("VC")
+                .join("Tools")
+                .join("MSVC")
+                .join(toolset)
+                .join("bin");
+            files.push(bin.join(format!("Host{arch}")).join(arch).join("cl.exe"));
+        }
+        for file in files {
+            fs::create_dir_all(file.parent().expect("failed")).expect("failed");
+            fs::write(file, b"").expect("failed");
+        }
+        fs::write(
+            build.join("Microsoft.VCToolsVersion.default.txt"),
+            format!("{}\r\n", toolsets[0]),
+        )
+        .expect("failed");
+        let mut json: Value = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        json[0]["installationPath"] = root.to_string_lossy().into();
+        (root, json.to_string())
     }
 
-    /// Returns `true` if a Visual Studio instance is a prerelease version, or `false` otherwise.
-    pub fn is_prerelease(&self) -> bool {
-        self.is_prerelease
+    #[test]
+    fn test_find_vs_tools() {
+        let (root, json) = fake_installation("vswhere-rs-test-vs-tools", &["14.29.30133"]);
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(json));
+        let tools = config
+            .find_vs_tools_custom_path("vswhere.exe")
+            .expect("failed")
+            .expect("no tools found");
+        assert_eq!(tools.installation_path(), root);
+        assert!(tools.msbuild().expect("no MSBuild").is_file());
+        assert!(tools.vcvarsall().expect("no vcvarsall.bat").is_file());
+        assert_eq!(tools.vc_tools_version(), Some("14.29.30133"));
+        assert!(tools.host_compiler().expect("no cl.exe").is_file());
+        let _ = config.runner(MockRunner::new("[]"));
+        assert_eq!(
+            config
+                .find_vs_tools_custom_path("vswhere.exe")
+                .expect("failed"),
+            None
+        );
+        fs::remove_dir_all(&root).expect("failed");
     }
 
-    /// Returns the human-readable name of a Visual Studio instance.
-    pub fn display_name(&self) -> &str {
-        &self.display_name
+    #[test]
+    fn test_find_cl() {
+        let paths = [
+            "C:/VS/VC/Tools/MSVC/14.16.27023/bin/Hostx64/arm64/cl.exe",
+            "C:/VS/VC/Tools/MSVC/14.29.30133/bin/Hostx64/arm64/cl.exe",
+            "C:/VS/VC/Tools/MSVC/14.28.29910/bin/Hostx64/arm64/cl.exe",
+        ];
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(
+            serde_json::to_string(&paths).expect("failed"),
+        ));
+        let found = config
+            .find_cl_custom_path("vswhere.exe", Arch::X64, Arch::Arm64)
+            .expect("failed");
+        assert_eq!(found.as_deref(), Some(Path::new(paths[1])));
+        let _ = config.runner(MockRunner::new("[]"));
+        let found = config
+            .find_cl_custom_path("vswhere.exe", Arch::X86, Arch::Arm)
+            .expect("failed");
+        assert_eq!(found, None);
     }
 
-    /// Returns the human-readable description for a Visual Studio instance.
-    pub fn description(&self) -> &str {
-        &self.description
+    #[test]
+    fn test_msvc_toolset_versions() {
+        let toolsets = ["14.29.30133", "14.16.27023", "14.30.30705"];
+        let (root, json) = fake_installation("vswhere-rs-test-toolsets", &toolsets);
+        fs::create_dir_all(root.join("VC/Tools/MSVC/not-a-version")).expect("failed");
+        let mut instances = parse_instances(json.as_bytes()).expect("failed");
+        let versions = instances[0].msvc_toolset_versions().expect("failed");
+        let expected: Vec<FourPointVersion> = ["14.30.30705", "14.29.30133", "14.16.27023"]
+            .iter()
+            .map(|v| v.parse().expect("failed"))
+            .collect();
+        assert_eq!(versions, expected);
+        fs::remove_dir_all(&root).expect("failed");
+        assert!(instances
+            .remove(0)
+            .msvc_toolset_versions()
+            .expect("failed")
+            .is_empty());
     }
 
-    /// Returns the ID of the release channel that a Visual Studio instance is associated with.
-    pub fn channel_id(&self) -> &str {
-        &self.channel_id
+    #[test]
+    fn test_extract() {
+        let exe = b"not really vswhere";
+        let vswhere = VsWhere::extract(exe).expect("failed");
+        assert_eq!(fs::read(vswhere.path()).expect("failed"), exe);
+        assert_eq!(VsWhere::extract(exe).expect("failed"), vswhere);
+        fs::remove_file(vswhere.path()).expect("failed");
+
+        let exe = b"not really vswhere either";
+        let threads: Vec<_> = (0..8)
+            .map(|_| thread::spawn(move || VsWhere::extract(exe)))
+            .collect();
+        let extracted: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().expect("panicked").expect("failed"))
+            .collect();
+        assert!(extracted.iter().all(|vswhere| *vswhere == extracted[0]));
+        assert_eq!(fs::read(extracted[0].path()).expect("failed"), exe);
+        fs::remove_file(extracted[0].path()).expect("failed");
     }
 
-    /// Returns the filesystem path to the catalog file for the release channel that a Visual
-    /// Studio instance is associated with.
-    pub fn channel_path(&self) -> &Path {
-        &self.channel_path
+    #[test]
+    fn test_everything() {
+        let args = Config::everything().args();
+        assert_eq!(args[..4], ["-prerelease", "-products", "*", "-all"]);
     }
 
-    /// Returns the URL from where release channel updates are fetched.
-    pub fn channel_url(&self) -> &Url {
-        &self.channel_uri
+    #[test]
+    fn test_color() {
+        let nocolor = |config: &Config, format| {
+            config
+                .command_with_format("vswhere.exe", format)
+                .get_args()
+                .any(|arg| arg == "-nocolor")
+        };
+        let mut config = Config::new();
+        assert!(!nocolor(&config, OutputFormat::Json));
+        assert!(nocolor(&config, OutputFormat::Text));
+        assert!(nocolor(&config, OutputFormat::Value));
+        let _ = config.color(true);
+        assert!(!nocolor(&config, OutputFormat::Text));
     }
 
-    /// {TODO}
-    pub fn engine_path(&self) -> &Path {
-        &self.engine_path
+    #[test]
+    fn test_validate() {
+        let v2017 = FourPointVersion::major_minor(15, 0);
+        let v2019 = FourPointVersion::major_minor(16, 0);
+        let mut config = Config::new();
+        let _ = config
+            .version_range(v2017..=v2019)
+            .runner(MockRunner::new(SAMPLE_JSON));
+        assert_eq!(config.validate(), Ok(()));
+        let _ = config.version_range(v2019..v2017);
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvertedVersionRange(v2019, v2017))
+        );
+        match config.run_custom_path("vswhere.exe") {
+            Err(Error::Config(ConfigError::InvertedVersionRange(..))) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let _ = config.version_range(v2017..v2017);
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::EmptyVersionRange(v2017))
+        );
+        let _ = config.version_range(v2017..=v2017);
+        assert_eq!(config.validate(), Ok(()));
     }
 
-    /// Returns the URL to the release notes for a Visual Studio instance.
-    pub fn release_notes(&self) -> &Url {
-        &self.release_notes
+    #[test]
+    fn test_with_raw() {
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(SAMPLE_JSON));
+        let (instances, raw) = config
+            .run_custom_path_with_raw("vswhere.exe")
+            .expect("failed");
+        assert_eq!(raw, SAMPLE_JSON);
+        assert_eq!(
+            to_value(instances),
+            to_value(parse_instances(raw.as_bytes()).expect("failed"))
+        );
     }
 
-    /// Returns the URL to the third-party notices for a Visual Studio instance.
-    pub fn third_party_notices(&self) -> &Url {
-        &self.third_party_notices
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn test_run_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed");
+        let v2017 = FourPointVersion::major_minor(15, 0);
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(SAMPLE_JSON));
+        let instances = runtime
+            .block_on(config.run_custom_path_async("vswhere.exe"))
+            .expect("failed");
+        assert_eq!(instances[0].instance_id(), "2a9d4c4e");
+        let _ = config.version_range(v2017..v2017);
+        assert!(matches!(
+            runtime.block_on(config.run_custom_path_async("vswhere.exe")),
+            Err(Error::Config(ConfigError::EmptyVersionRange(_)))
+        ));
     }
 
-    /// Returns the date and time when a Visual Studio instance was last updated.
-    pub fn update_date(&self) -> &DateTime<Utc> {
-        &self.update_date
+    #[test]
+    fn test_validate_for() {
+        let v2_5_2 = Version::parse("2.5.2+gebb9f26a3").expect("failed");
+        let mut config = Config::new();
+        assert_eq!(config.validate_for(&v2_5_2), Ok(()));
+        assert_eq!(
+            config.validate_for(&Version::new(2, 4, 0)),
+            Err(ConfigError::UnsupportedFlag("-utf8", Version::new(2, 5, 2)))
+        );
+        let _ = config.sort_newest_first(true);
+        assert_eq!(
+            config.validate_for(&v2_5_2),
+            Err(ConfigError::UnsupportedFlag("-sort", Version::new(2, 7, 1)))
+        );
+        let _ = config
+            .sort_newest_first(false)
+            .utf8(false)
+            .prerelease_filter(PrereleaseFilter::Include);
+        assert_eq!(
+            config.validate_for(&Version::new(1, 0, 62)),
+            Err(ConfigError::UnsupportedFlag(
+                "-prerelease",
+                Version::new(2, 0, 2)
+            ))
+        );
+        assert_eq!(config.validate_for(&Version::new(2, 4, 0)), Ok(()));
+        assert_eq!(
+            min_vswhere_version("-REQUIRESANY"),
+            Some(Version::new(2, 3, 2))
+        );
+        assert_eq!(min_vswhere_version("/NoColor"), Some(Version::new(2, 8, 4)));
+        assert_eq!(min_vswhere_version("-latest"), None);
+        assert_eq!(min_vswhere_version("utf8"), None);
     }
 
-    /// Returns the catalog information for a Visual Studio instance.
-    pub fn catalog(&self) -> &InstallCatalog {
-        &self.catalog
+    #[test]
+    fn test_channel() {
+        assert_eq!(
+            Channel::from_channel_id("VisualStudio.16.Preview"),
+            Some(Channel::Preview)
+        );
+        assert_eq!(
+            Channel::from_channel_id("visualstudio.17.release.ltsc.17.8"),
+            Some(Channel::Release)
+        );
+        assert_eq!(Channel::from_channel_id("VisualStudio.16"), None);
+        assert_eq!(Channel::from_channel_id("VisualStudio.16.Canary"), None);
     }
 
-    /// Returns the property information for a Visual Studio instance.
-    pub fn properties(&self) -> &InstallProperties {
-        &self.properties
+    #[test]
+    fn test_with_warnings() {
+        let mut runner = MockRunner::new(SAMPLE_JSON);
+        let _ = runner.stderr("Warning: deprecated\r\n\r\n");
+        let mut config = Config::new();
+        let _ = config.runner(runner);
+        let (instances, warnings) = config
+            .run_custom_path_with_warnings("vswhere.exe")
+            .expect("failed");
+        assert_eq!(instances.len(), 1);
+        assert_eq!(warnings, ["Warning: deprecated"]);
     }
-}
 
-impl InstallCatalog {
-    /// {TODO}
-    pub fn build_branch(&self) -> &str {
-        &self.build_branch
+    #[test]
+    fn test_utf8() {
+        let mut config = Config::new();
+        assert!(config.args().contains(&"-utf8".into()));
+        assert_eq!(config.output_code_page(), None);
+        let _ = config.utf8(false);
+        assert!(!config.args().contains(&"-utf8".into()));
+        assert_eq!(config.output_code_page(), Some(0));
+        let _ = config.code_page(Some(65001));
+        assert_eq!(config.output_code_page(), Some(65001));
+        let _ = config.runner(MockRunner::new(SAMPLE_JSON));
+        let instances = config.run_custom_path("vswhere.exe").expect("failed");
+        assert_eq!(instances[0].instance_id(), "2a9d4c4e");
     }
 
-    /// {TODO}
-    pub fn build_version(&self) -> &FourPointVersion {
-        &self.build_version
+    #[test]
+    fn test_display_version() {
+        let version = FourPointVersion::new(16, 11, 2, 0);
+        assert_eq!(version.to_string(), "16.11.2.0");
+        assert_eq!(format!("{version:#}"), "16.11");
     }
 
-    /// {TODO}
-    pub fn id(&self) -> &str {
-        &self.id
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            "15.7.27703.2018".parse(),
+            Ok(FourPointVersion::new(15, 7, 27703, 2018))
+        );
+        assert_eq!("15.7".parse(), Ok(FourPointVersion::new(15, 7, 0, 0)));
+        assert!("15.seven".parse::<FourPointVersion>().is_err());
+        assert!("15.70000".parse::<FourPointVersion>().is_err());
+        assert!("1.2.3.4.5".parse::<FourPointVersion>().is_err());
+        assert!("".parse::<FourPointVersion>().is_err());
     }
 
-    /// {TODO}
-    pub fn local_build(&self) -> &str {
-        &self.local_build
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_instance_id() {
+        let config = Config::new();
+        for instance in config.run_default_path().expect("failed") {
+            let found = config
+                .run_default_path_by_instance_id(instance.instance_id())
+                .expect("failed");
+            assert_eq!(to_value(found), to_value(Some(&instance)));
+        }
     }
 
-    /// {TODO}
-    pub fn manifest_name(&self) -> &str {
-        &self.manifest_name
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_refresh() {
+        for instance in Config::new().run_default_path().expect("failed") {
+            let refreshed = instance.refresh_default_path().expect("failed");
+            assert_eq!(to_value(refreshed), to_value(Some(&instance)));
+        }
     }
 
-    /// {TODO}
-    pub fn manifest_type(&self) -> &str {
-        &self.manifest_type
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_custom_type() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Instance {
+            installation_path: PathBuf,
+        }
+
+        let instances: Vec<Instance> = Config::new().run_default_path_as().expect("failed");
+        for instance in instances {
+            assert!(instance.installation_path.is_absolute());
+        }
     }
 
-    /// Returns the human-readable version number for a Visual Studio instance.
-    pub fn product_display_version(&self) -> &str {
-        &self.product_display_version
+    #[test]
+    fn test_product_line() {
+        assert_eq!(ProductLine::from_year(2019), Some(ProductLine::Vs2019));
+        assert_eq!(ProductLine::from_year(2018), None);
+        assert_eq!(
+            ProductLine::Vs2022.version_range(),
+            FourPointVersion::new(17, 0, 0, 0)..FourPointVersion::new(18, 0, 0, 0)
+        );
     }
 
-    /// {TODO}
-    pub fn product_line(&self) -> &str {
-        &self.product_line
+    #[test]
+    fn test_edition() {
+        assert_eq!(
+            Edition::from_product_id(ids::PRODUCT_ENTERPRISE),
+            Some(Edition::Enterprise)
+        );
+        assert_eq!(
+            Edition::from_product_id("buildtools"),
+            Some(Edition::BuildTools)
+        );
+        assert_eq!(Edition::from_product_id("Express"), None);
+        let mut config = Config::new();
+        let _ = config
+            .whitelist_edition(Edition::BuildTools)
+            .whitelist_components(&[Component::VcToolsX86X64, Component::Windows10Sdk]);
+        let args = config.args();
+        assert_eq!(args[..2], ["-products", ids::PRODUCT_BUILD_TOOLS]);
+        assert_eq!(
+            args[2..5],
+            [
+                "-requires",
+                ids::COMPONENT_VC_TOOLS_X86_X64,
+                ids::COMPONENT_WINDOWS_10_SDK
+            ]
+        );
+        let instances: Vec<InstallInfo> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        assert_eq!(instances[0].edition(), Some(Edition::Community));
     }
 
-    /// {TODO}
-    pub fn product_line_version(&self) -> &str {
-        &self.product_line_version
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_property() {
+        let config = Config::new();
+        let json = config
+            .run_property_default_path("catalog.productDisplayVersion")
+            .expect("failed");
+        let values = config
+            .run_property_values_default_path("catalog.productDisplayVersion")
+            .expect("failed");
+        assert_eq!(json, values);
     }
 
-    /// {TODO}
-    pub fn product_milestone(&self) -> &str {
-        &self.product_milestone
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_find() {
+        let _ = Config::new()
+            .run_find_default_path(r"Common7\IDE\**\*.exe")
+            .expect("failed");
     }
 
-    /// {TODO}
-    pub fn product_milestone_is_pre_release(&self) -> bool {
-        unimplemented!()
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_invalid_json() {
+        let path = env::temp_dir().join("vswhere-rs-invalid-json.bat");
+        fs::write(&path, "@echo not json").expect("failed");
+        let result = Config::new().run_custom_path(&path);
+        fs::remove_file(&path).expect("failed");
+        match result {
+            Err(Error::Parse(_)) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
     }
 
-    /// {TODO}
-    pub fn product_name(&self) -> &str {
-        &self.product_name
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_exit_stderr() {
+        match Config::new().raw_arg("-notAnOption").run_default_path() {
+            Err(Error::Exit { ref stderr, .. }) if !stderr.is_empty() => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
     }
 
-    /// {TODO}
-    pub fn product_patch_version(&self) -> &str {
-        &self.product_patch_version
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_timeout() {
+        let path = env::temp_dir().join("vswhere-rs-timeout.bat");
+        fs::write(&path, "@ping -n 5 127.0.0.1 > nul").expect("failed");
+        let result = Config::new()
+            .timeout(Some(Duration::from_millis(100)))
+            .run_custom_path(&path);
+        fs::remove_file(&path).expect("failed");
+        match result {
+            Err(Error::TimedOut) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
     }
 
-    /// {TODO}
-    pub fn product_pre_release_milestone_suffix(&self) -> &str {
-        &self.product_pre_release_milestone_suffix
+    #[test]
+    #[cfg_attr(not(unix), ignore = "requires a Unix shell")]
+    fn test_timeout_with_grandchild() {
+        let mut cmd = Command::new("sh");
+        let _ = cmd.args(["-c", "sleep 5 & sleep 5"]);
+        let start = Instant::now();
+        match output_with_timeout(&mut cmd, Duration::from_millis(100)) {
+            Err(Error::TimedOut) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+        let output =
+            output_with_timeout(Command::new("echo").arg("vswhere"), Duration::from_secs(5))
+                .expect("failed");
+        assert_eq!(output.stdout, b"vswhere\n");
     }
 
-    /// {TODO}
-    pub fn product_release(&self) -> &str {
-        &self.product_release
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_find_msbuild() {
+        if let Some(path) = Config::new().find_msbuild_default_path().expect("failed") {
+            assert!(path.is_file());
+        }
     }
 
-    /// Returns the semver-compliant version number for a Visual Studio instance.
-    pub fn product_semantic_version(&self) -> &Version {
-        &self.product_semantic_version
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_find_dev_env_scripts() {
+        let config = Config::new();
+        if let Some(path) = config.find_vcvarsall_default_path().expect("failed") {
+            assert!(path.ends_with(r"VC\Auxiliary\Build\vcvarsall.bat"));
+        }
+        if let Some(path) = config.find_vsdevcmd_default_path().expect("failed") {
+            assert!(path.ends_with(r"Common7\Tools\VsDevCmd.bat"));
+        }
     }
 
-    /// {TODO}
-    pub fn required_engine_version(&self) -> &FourPointVersion {
-        &self.required_engine_version
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_raw() {
+        let json = Config::new()
+            .run_raw_default_path(OutputFormat::Json)
+            .expect("failed");
+        assert!(json.trim_start().starts_with('['));
+        let _ = Config::new()
+            .run_raw_default_path(OutputFormat::Text)
+            .expect("failed");
     }
-}
 
-impl InstallProperties {
-    /// {TODO}
-    pub fn campaign_id(&self) -> &str {
-        &self.campaign_id
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_locate() {
+        let vswhere = VsWhere::locate().expect("failed");
+        let config = Config::new();
+        assert_eq!(
+            to_value(vswhere.run(&config).expect("failed")),
+            to_value(config.run_default_path().expect("failed"))
+        );
     }
 
-    /// {TODO}
-    pub fn channel_manifest_id(&self) -> &str {
-        &self.channel_manifest_id
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_count() {
+        let config = Config::new();
+        let count = config.count_default_path().expect("failed");
+        assert_eq!(count, config.run_default_path().expect("failed").len());
+        assert_eq!(config.any_default_path().expect("failed"), count != 0);
     }
 
-    /// Returns the user-assigned nickname for a Visual Studio instance.
-    pub fn nickname(&self) -> &str {
-        &self.nickname
+    #[test]
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_default_path_candidates() {
+        let candidates = default_path_candidates().expect("failed");
+        assert!(!candidates.is_empty());
+        let vswhere = VsWhere::locate().expect("failed");
+        assert!(candidates.iter().any(|path| path == vswhere.path()));
+        let all = VsWhere::locate_all().expect("failed");
+        assert_eq!(all.first(), Some(&vswhere));
+        assert!(all[1..].iter().all(|vswhere| vswhere.path().is_file()));
     }
 
-    /// {TODO}
-    pub fn setup_engine_file_path(&self) -> &Path {
-        &self.setup_engine_file_path
+    #[test]
+    #[cfg(not(windows))]
+    fn test_unsupported() {
+        match Config::new().run_default_path() {
+            Err(Error::Spawn(ref e)) if e.kind() == ErrorKind::Unsupported => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use {Config, FourPointVersion};
+    #[test]
+    fn test_parse_vswhere_version() {
+        let banner =
+            "Visual Studio Locator version 2.5.2+gebb9f26a3 [query version 1.16.1187.57215]\n\
+                      Copyright (C) Microsoft Corporation. All rights reserved.\n";
+        assert_eq!(
+            parse_vswhere_version(banner),
+            Some(Version::parse("2.5.2+gebb9f26a3").expect("failed"))
+        );
+        assert_eq!(parse_vswhere_version("Usage: vswhere.exe [options]"), None);
+
+        let mut config = Config::new();
+        let _ = config.runner(MockRunner::new(banner));
+        assert_eq!(
+            config
+                .vswhere_version_custom_path("vswhere.exe")
+                .expect("failed"),
+            Version::parse("2.5.2+gebb9f26a3").expect("failed")
+        );
+        let _ = config.runner(MockRunner::new("Usage: vswhere.exe [options]"));
+        match config.vswhere_version_custom_path("vswhere.exe") {
+            Err(Error::UnknownVersion(ref banner)) if banner.starts_with("Usage") => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        let mut runner = MockRunner::new(banner);
+        let _ = runner.exit_code(87);
+        let _ = config.runner(runner);
+        match config.vswhere_version_custom_path("vswhere.exe") {
+            Err(Error::Exit { code: Some(87), .. }) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
 
     #[test]
-    fn test_default() {
-        let _ = Config::default().run_default_path().expect("failed");
+    #[cfg_attr(not(windows), ignore = "vswhere is only available on Windows")]
+    fn test_vswhere_version() {
+        let version = vswhere_version_default_path().expect("failed");
+        assert!(version >= Version::new(2, 5, 2));
     }
 
     #[test]
-    fn test_args() {
-        let _ = Config::new()
-            .find_prerelease_versions(true)
-            .whitelist_product_id("*")
-            .whitelist_component_id("Microsoft.VisualStudio.Component.VC.Tools.x86.x64")
-            .require_any_component(true)
-            .version_number_range(
-                FourPointVersion::new(
-                    u16::min_value(),
-                    u16::min_value(),
-                    u16::min_value(),
-                    u16::min_value(),
-                )
-                    ..FourPointVersion::new(
-                        u16::max_value(),
-                        u16::max_value(),
-                        u16::max_value(),
-                        u16::max_value(),
-                    ),
-            )
-            .only_latest_versions(true)
-            .run_default_path()
-            .expect("failed");
+    fn test_extra_fields() {
+        let instances: Vec<InstallInfo> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        assert_eq!(instances.len(), 1);
+        let extra = instances[0].extra();
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["state"], 4_294_967_295_u32);
+        assert!(instances[0].is_complete());
+        assert!(instances[0].is_launchable());
+        assert!(!instances[0].is_reboot_required());
+        assert!(instances[0].is_usable());
+        assert_eq!(instances[0].build_metadata(), Some("27703.2018"));
+        assert_eq!(instances[0].channel(), Some(Channel::Release));
+        let catalog = instances[0].catalog();
+        assert_eq!(catalog.product_line_version(), "2017");
+        assert!(!catalog.product_milestone_is_pre_release());
+        assert_eq!(catalog.extra().len(), 1);
+        let properties = instances[0].properties();
+        assert_eq!(
+            properties.channel_manifest_id(),
+            "VisualStudio.15.Release/15.7.2+27703.2018"
+        );
+        assert!(properties.extra().is_empty());
     }
 
     #[test]
-    fn test_fake_product() {
-        let _ = Config::new()
-            .whitelist_product_id("The quick brown fox jumps over the lazy dog.")
-            .run_default_path()
-            .expect("failed");
+    fn test_prerelease_filter() {
+        let instances: Vec<InstallInfo> = serde_json::from_str(SAMPLE_JSON).expect("failed");
+        assert!(!instances[0].is_prerelease());
+        assert_eq!(
+            filter_instances(PrereleaseFilter::Include, false, instances.clone()).len(),
+            1
+        );
+        assert!(filter_instances(PrereleaseFilter::Only, false, instances).is_empty());
     }
 }